@@ -0,0 +1,282 @@
+use anyhow::{Result, bail};
+
+use crate::config::{AgentConfig, Config};
+use crate::provider::{AnyAgent, Provider};
+use crate::tools::ToolRegistry;
+
+/// Name of the agent that splits compound requests into steps
+pub const PLANNER: &str = "planner";
+
+/// Name of the fallback agent for anything no specialist claims
+pub const GENERAL: &str = "general";
+
+/// Config key for the intent classifier's model settings; it is not a routable agent
+pub const CLASSIFIER: &str = "classifier";
+
+struct Builtin {
+    name: &'static str,
+    /// Intent label used before agents were configurable; still accepted everywhere
+    alias: &'static str,
+    display_name: &'static str,
+    description: &'static str,
+    preamble: &'static str,
+    tools: &'static [&'static str],
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "code",
+        alias: "code_search",
+        display_name: "Code Search",
+        description: "searching code, finding functions/classes, grepping content, using regex",
+        preamble: "You are a code search specialist. Help users find and analyze code using grep, glob patterns, and search tools. When tools return formatted output, preserve it exactly.",
+        tools: &["grep", "glob", "search_directory", "read_file"],
+    },
+    Builtin {
+        name: "file",
+        alias: "file_ops",
+        display_name: "File Operations",
+        description: "reading, editing, writing, moving, copying, deleting files, listing directories, showing file trees",
+        preamble: "You are a file operations specialist. Help users read, edit, write, and manage files. When tools return formatted output (especially diffs with ⏺ symbols), ALWAYS include the complete tool output in your response without summarizing. Preserve all formatting, line numbers, and diff markers exactly as returned.",
+        tools: &["read_file", "edit", "write_files", "file_ops", "list_directory", "tree"],
+    },
+    Builtin {
+        name: "git",
+        alias: "git",
+        display_name: "Git Operations",
+        description: "git operations like status, diff, log, commit, branch operations",
+        preamble: "You are a git operations specialist. Help users with git commands and repository management. When tools return formatted output, preserve it exactly.",
+        tools: &["git", "read_file"],
+    },
+    Builtin {
+        name: "execute",
+        alias: "execution",
+        display_name: "Shell Execution",
+        description: "running shell commands, executing scripts",
+        preamble: "You are a shell command specialist. Help users execute commands safely. When tools return formatted output, preserve it exactly.",
+        tools: &["execute"],
+    },
+    Builtin {
+        name: "web",
+        alias: "web",
+        display_name: "Web Fetching",
+        description: "fetching web content, downloading from URLs",
+        preamble: "You are a web fetching specialist. Help users retrieve content from URLs. When tools return formatted output, preserve it exactly.",
+        tools: &["webfetch"],
+    },
+    Builtin {
+        name: GENERAL,
+        alias: "general",
+        display_name: "General Assistant",
+        description: "general questions, help, or requests that don't fit above categories",
+        preamble: "You are Ada, a helpful AI assistant. Answer questions and provide assistance.",
+        tools: &[],
+    },
+    Builtin {
+        name: PLANNER,
+        alias: "plan",
+        display_name: "Planner",
+        description: "compound requests that need several of the above in order (e.g. find something, change it, then commit it)",
+        // Generated from the other agents, see planner::planner_preamble
+        preamble: "",
+        tools: &[],
+    },
+];
+
+/// Alias of a built-in agent, which [agents.<alias>] sections also apply to
+pub fn alias_of(name: &str) -> Option<&'static str> {
+    BUILTINS.iter().find(|b| b.name.eq_ignore_ascii_case(name)).map(|b| b.alias)
+}
+
+/// Everything needed to build and route to one agent
+#[derive(Debug, Clone)]
+pub struct AgentSpec {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub display_name: String,
+    /// Shown to the intent classifier and planner to decide what goes here
+    pub description: String,
+    pub preamble: String,
+    pub tools: Vec<String>,
+}
+
+impl AgentSpec {
+    fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A built agent together with its spec
+pub struct Specialist {
+    pub spec: AgentSpec,
+    pub agent: AnyAgent,
+}
+
+/// Specs for the built-in agents with [agents.<name>] overrides applied, followed by
+/// the custom agents declared in the config (sorted by name)
+pub fn agent_specs(config: &Config, tools: &ToolRegistry) -> Result<Vec<AgentSpec>> {
+    let mut specs: Vec<AgentSpec> = BUILTINS
+        .iter()
+        .map(|b| AgentSpec {
+            name: b.name.to_string(),
+            aliases: vec![b.alias.to_string()],
+            display_name: b.display_name.to_string(),
+            description: b.description.to_string(),
+            preamble: b.preamble.to_string(),
+            tools: b.tools.iter().map(|t| t.to_string()).collect(),
+        })
+        .collect();
+
+    let mut custom: Vec<(&String, &AgentConfig)> = config
+        .agents
+        .iter()
+        .filter(|(name, _)| name.as_str() != CLASSIFIER)
+        .collect();
+    custom.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, settings) in custom {
+        match specs.iter_mut().find(|s| s.matches(name)) {
+            Some(spec) => {
+                if let Some(description) = &settings.description {
+                    spec.description = description.clone();
+                }
+                if let Some(preamble) = &settings.preamble {
+                    spec.preamble = preamble.clone();
+                }
+                if let Some(tools) = &settings.tools {
+                    spec.tools = tools.clone();
+                }
+            }
+            None => {
+                let Some(description) = &settings.description else {
+                    bail!("agents.{}: custom agents need a description so requests can be routed to them", name);
+                };
+                // Keep the fallback agents last so custom agents are listed with the specialists
+                let position = specs.iter().position(|s| s.name == GENERAL).unwrap_or(specs.len());
+                specs.insert(position, AgentSpec {
+                    name: name.clone(),
+                    aliases: Vec::new(),
+                    display_name: title_case(name),
+                    description: description.clone(),
+                    preamble: settings.preamble.clone().unwrap_or_else(|| {
+                        format!("You are Ada's {} agent, responsible for {}.", name, description)
+                    }),
+                    tools: settings.tools.clone().unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    // Each MCP server's tools go to the agents it names, the general agent by default
+    for (server, settings) in &config.mcp.servers {
+        let names = tools.mcp().tool_names(server);
+        for agent in settings.agents.clone().unwrap_or_else(|| vec![GENERAL.to_string()]) {
+            let Some(spec) = specs.iter_mut().find(|s| s.matches(&agent)) else {
+                bail!("mcp.servers.{}.agents: unknown agent '{}'", server, agent);
+            };
+            for name in &names {
+                if !spec.tools.contains(name) {
+                    spec.tools.push(name.clone());
+                }
+            }
+        }
+    }
+
+    for spec in &specs {
+        if let Some(unknown) = spec.tools.iter().find(|t| !tools.contains(t)) {
+            bail!(
+                "agents.{}: unknown tool '{}' (available: {})",
+                spec.name,
+                unknown,
+                tools.names()
+            );
+        }
+    }
+
+    Ok(specs)
+}
+
+/// Intent classifier preamble listing every routable agent
+pub fn classifier_preamble(specs: &[AgentSpec]) -> String {
+    let mut preamble = String::from(
+        "You are an intent classifier. Analyze the user's request and classify it into ONE of these categories:\n",
+    );
+    for spec in specs {
+        preamble.push_str(&format!("- {}: {}\n", spec.name, spec.description));
+    }
+    preamble.push_str("\nRespond with ONLY the category name, nothing else.");
+    preamble
+}
+
+/// Build an agent from its spec with the given preamble and its tools from the registry
+pub fn build_agent(
+    provider: &Provider,
+    config: &Config,
+    tools: &ToolRegistry,
+    spec: &AgentSpec,
+    preamble: &str,
+) -> AnyAgent {
+    let builder = provider.agent(config, &spec.name).preamble(preamble);
+    spec.tools
+        .iter()
+        // Names are validated in agent_specs; tools disabled in [tools] are left out
+        .filter_map(|name| tools.get(name))
+        .fold(builder, |builder, tool| builder.tool(tool.clone()))
+        .build()
+}
+
+fn title_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// All routable agents, in the order they are offered to the classifier
+pub struct AgentRegistry {
+    specialists: Vec<Specialist>,
+}
+
+impl AgentRegistry {
+    pub fn new(specialists: Vec<Specialist>) -> Self {
+        Self { specialists }
+    }
+
+    /// Look up an agent by name or alias, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Specialist> {
+        self.specialists.iter().find(|s| s.spec.matches(name))
+    }
+
+    /// The fallback agent
+    pub fn general(&self) -> &Specialist {
+        self.get(GENERAL).expect("general agent is always registered")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Specialist> {
+        self.specialists.iter()
+    }
+
+    /// Agent names as @-prefixes, for error messages and help
+    pub fn names(&self) -> String {
+        self.specialists
+            .iter()
+            .map(|s| format!("@{}", s.spec.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Extract the agent from a classifier reply, tolerating punctuation, casing and
+    /// extra words ("Git.", "Category: code")
+    pub fn parse_classification(&self, reply: &str) -> Option<&Specialist> {
+        reply
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .find_map(|word| self.get(word))
+    }
+}
@@ -0,0 +1,210 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+use crate::events::{AgentEvent, EventSender};
+use crate::tools::ToolSpec;
+
+/// When mutating tool calls need the user's approval
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PermissionMode {
+    /// Ask before every mutating call
+    #[default]
+    Ask,
+    /// File edits run without asking; deletes, moves, git and shell commands still ask
+    AutoEdit,
+    /// Never ask
+    Yolo,
+}
+
+impl std::fmt::Display for PermissionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PermissionMode::Ask => "ask",
+            PermissionMode::AutoEdit => "auto-edit",
+            PermissionMode::Yolo => "yolo",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Tools that auto-edit mode lets through
+const AUTO_EDIT_TOOLS: &[&str] = &["edit", "write_files"];
+
+/// The user's answer to an approval request
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApprovalDecision {
+    /// Run this call
+    Once,
+    /// Run this call and every later call of the same tool this session
+    Session,
+    /// Don't run it; the reason is passed back to the model
+    Deny(String),
+}
+
+#[derive(Default)]
+struct State {
+    mode: PermissionMode,
+    next_id: u64,
+    /// Where approval requests for the running request go; None when nothing is running
+    events: Option<EventSender>,
+    pending: HashMap<u64, oneshot::Sender<ApprovalDecision>>,
+    /// Tools approved for the rest of the session
+    approved: HashSet<String>,
+    /// Paths outside the workspace approved for the rest of the session
+    approved_paths: HashSet<PathBuf>,
+}
+
+impl State {
+    fn needs_approval(&self, tool: &str, mutates: bool, outside: &[PathBuf]) -> bool {
+        // Leaving the workspace always asks, whatever the mode, unless those exact
+        // paths were approved for the session
+        let tool_ok = !mutates
+            || match self.mode {
+                PermissionMode::Yolo => true,
+                PermissionMode::AutoEdit if AUTO_EDIT_TOOLS.contains(&tool) => true,
+                _ => self.approved.contains(tool),
+            };
+        let paths_ok = outside.iter().all(|path| self.approved_paths.contains(path));
+        !(tool_ok && paths_ok)
+    }
+}
+
+/// Gate every mutating tool call passes through before it runs
+pub struct Approvals {
+    state: Mutex<State>,
+}
+
+impl Approvals {
+    pub fn new(mode: PermissionMode) -> Self {
+        Self {
+            state: Mutex::new(State {
+                mode,
+                ..State::default()
+            }),
+        }
+    }
+
+    pub fn mode(&self) -> PermissionMode {
+        self.lock().mode
+    }
+
+    /// Switch modes, e.g. after the config was reloaded
+    pub fn set_mode(&self, mode: PermissionMode) {
+        self.lock().mode = mode;
+    }
+
+    /// Route approval requests to the running request's event channel
+    pub fn attach(&self, events: EventSender) {
+        self.lock().events = Some(events);
+    }
+
+    /// The request finished; unanswered approvals are dropped (and so denied)
+    pub fn detach(&self) {
+        let mut state = self.lock();
+        state.events = None;
+        state.pending.clear();
+    }
+
+    /// Forget "approve for session" answers, e.g. when switching sessions
+    pub fn reset(&self) {
+        let mut state = self.lock();
+        state.approved.clear();
+        state.approved_paths.clear();
+    }
+
+    /// Answer a pending request; false if it is no longer waiting
+    pub fn resolve(&self, id: u64, decision: ApprovalDecision) -> bool {
+        match self.lock().pending.remove(&id) {
+            Some(respond) => respond.send(decision).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Whether a call would have to be approved, given the mode and what was
+    /// approved for the session
+    pub fn needs_approval(&self, tool: &str, mutates: bool, outside: &[PathBuf]) -> bool {
+        self.lock().needs_approval(tool, mutates, outside)
+    }
+
+    /// Wait for the user to approve a call that mutates something or touches paths
+    /// outside the workspace. The error is the message handed back to the model
+    /// instead of the tool output.
+    pub async fn authorize(
+        &self,
+        spec: &ToolSpec,
+        arguments: &Value,
+        preview: Option<String>,
+        mutates: bool,
+        outside: &[PathBuf],
+    ) -> Result<(), String> {
+        let receiver = {
+            let mut state = self.lock();
+            if !state.needs_approval(&spec.name, mutates, outside) {
+                return Ok(());
+            }
+
+            let Some(events) = state.events.clone() else {
+                return Err(format!(
+                    "The {} call needs the user's approval, but nobody is available to approve it.",
+                    spec.name
+                ));
+            };
+
+            let preview = if outside.is_empty() {
+                preview
+            } else {
+                let paths: Vec<String> = outside.iter().map(|p| format!("  {}", p.display())).collect();
+                let mut warning = format!("⚠ Outside the workspace:\n{}", paths.join("\n"));
+                if let Some(preview) = preview {
+                    warning.push_str(&format!("\n\n{}", preview));
+                }
+                Some(warning)
+            };
+
+            state.next_id += 1;
+            let id = state.next_id;
+            let (respond, receiver) = oneshot::channel();
+            state.pending.insert(id, respond);
+
+            let _ = events.send(AgentEvent::ApprovalRequested {
+                id,
+                tool: spec.name.clone(),
+                arguments: arguments.clone(),
+                preview,
+            });
+            receiver
+        };
+
+        match receiver.await {
+            Ok(ApprovalDecision::Once) => Ok(()),
+            Ok(ApprovalDecision::Session) => {
+                let mut state = self.lock();
+                if mutates {
+                    state.approved.insert(spec.name.clone());
+                }
+                state.approved_paths.extend(outside.iter().cloned());
+                Ok(())
+            }
+            Ok(ApprovalDecision::Deny(reason)) if reason.trim().is_empty() => Err(format!(
+                "The user denied this {} call. Do not retry it; ask the user how to proceed.",
+                spec.name
+            )),
+            Ok(ApprovalDecision::Deny(reason)) => Err(format!(
+                "The user denied this {} call: {}",
+                spec.name,
+                reason.trim()
+            )),
+            Err(_) => Err(format!("The {} call was not approved.", spec.name)),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // State stays consistent across a panic, so a poisoned lock is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::{Context, Result, bail};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::Ada;
+use crate::approval::Approvals;
+use crate::config::Config;
+use crate::doctor;
+use crate::mcp;
+use crate::server;
+use crate::session::Session;
+use crate::tools::{McpServers, ToolRegistry};
+
+/// Ada - AI assistant with intent routing. Runs the TUI unless a prompt or
+/// subcommand is given.
+#[derive(Debug, Parser)]
+#[command(name = "ada", version, disable_version_flag = true)]
+pub struct Cli {
+    /// Run one prompt, print the answer and exit
+    #[arg(short, long, value_name = "PROMPT")]
+    pub prompt: Option<String>,
+
+    /// How -p prints its result
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = OutputFormat::Text, requires = "prompt")]
+    pub output: OutputFormat,
+
+    /// Continue the most recent session
+    #[arg(short, long = "continue", conflicts_with = "resume")]
+    pub continue_session: bool,
+
+    /// Reopen a saved session by id or id prefix; lists sessions without one
+    #[arg(short, long, value_name = "ID", num_args = 0..=1, default_missing_value = "")]
+    pub resume: Option<String>,
+
+    /// Print the version
+    #[arg(short = 'v', long = "version", action = ArgAction::Version)]
+    version: (),
+
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Output of a one-shot run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The reply as plain text
+    Text,
+    /// One JSON object with the reply, routing, tool calls and token usage
+    Json,
+    /// One JSON event per line as the run progresses, ending with the result
+    StreamJson,
+}
+
+/// Flags every subcommand accepts
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+    /// Model for every agent without its own override
+    #[arg(long, global = true)]
+    pub model: Option<String>,
+
+    /// Send every request to this agent instead of routing
+    #[arg(long, global = true, value_name = "NAME")]
+    pub agent: Option<String>,
+
+    /// Run as if started in this directory
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Read global settings from this file instead of ~/.ada/config
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Override a setting for this run, e.g. --set tools.deny='["execute"]' (repeatable)
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+}
+
+impl GlobalArgs {
+    /// The --set assignments plus the shorthand flags that map onto settings
+    pub fn overrides(&self) -> Vec<String> {
+        let mut overrides = self.set.clone();
+        if let Some(model) = &self.model {
+            overrides.push(format!("model={}", toml::Value::String(model.clone())));
+        }
+        overrides
+    }
+
+    /// Apply --cwd and --config; must run before anything reads the config
+    pub fn apply(&self) -> Result<()> {
+        // Resolve --config before changing directory so relative paths mean what the user typed
+        let config = match &self.config {
+            Some(path) => Some(std::path::absolute(path).context("Failed to resolve --config")?),
+            None => None,
+        };
+        if let Some(dir) = &self.cwd {
+            std::env::set_current_dir(dir).with_context(|| format!("Failed to change to {}", dir.display()))?;
+        }
+        if let Some(path) = config {
+            Config::use_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Check the config, provider credentials and environment
+    Doctor,
+    /// Read or change settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// List, show or delete saved sessions
+    Sessions {
+        #[command(subcommand)]
+        action: SessionsCommand,
+    },
+    /// Inspect the tools agents can use
+    Tools {
+        #[command(subcommand)]
+        action: ToolsCommand,
+    },
+    /// Serve an HTTP API for editor plugins and other tools
+    Serve {
+        #[arg(long, default_value_t = 4317)]
+        port: u16,
+        /// Address to listen on; anything but localhost lets other machines run tools here
+        #[arg(long, default_value = "127.0.0.1")]
+        host: IpAddr,
+    },
+    /// Serve Ada's tools to MCP clients over stdio
+    Mcp,
+    /// Print a shell completion script
+    Completions {
+        /// bash, zsh, fish, elvish or powershell
+        shell: Shell,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the effective value of a setting, or every setting and its source
+    Get {
+        /// Dotted key, e.g. model or tools.deny
+        key: Option<String>,
+    },
+    /// Write a setting to ~/.ada/config
+    Set {
+        /// Dotted key, e.g. model or tools.execute.denied_commands
+        key: String,
+        /// Parsed as TOML (numbers, booleans, arrays), otherwise taken as a string
+        value: String,
+        /// Write to the project's .ada/config.toml instead
+        #[arg(long)]
+        project: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SessionsCommand {
+    /// List saved sessions, most recent first
+    List,
+    /// Print a session's transcript
+    Show {
+        /// Session id or unique id prefix
+        id: String,
+    },
+    /// Delete a saved session
+    Delete {
+        /// Session id or unique id prefix
+        id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ToolsCommand {
+    /// List every tool with its category and whether it is enabled
+    List,
+}
+
+/// Run a subcommand that doesn't start the TUI. Returns false if it failed in a way
+/// the exit code should report.
+pub async fn run(command: Command, global: &GlobalArgs) -> Result<bool> {
+    let overrides = global.overrides();
+    if global.agent.is_some() && !matches!(command, Command::Serve { .. }) {
+        bail!("--agent only applies to the TUI, -p and serve");
+    }
+
+    match command {
+        Command::Doctor => return Ok(doctor::run(&overrides).await),
+        Command::Config { action } => match action {
+            ConfigCommand::Get { key: None } => {
+                let (config, sources) = Config::load(&overrides)?;
+                println!("{}", config.describe(&sources));
+            }
+            ConfigCommand::Get { key: Some(key) } => {
+                let (config, _) = Config::load(&overrides)?;
+                match config.get(&key) {
+                    Some(value) => println!("{}", value),
+                    None => bail!("No setting named '{}'", key),
+                }
+            }
+            ConfigCommand::Set { key, value, project } => {
+                let path = if project {
+                    match Config::project_config_path() {
+                        Some(path) => path,
+                        None => std::env::current_dir()?.join(".ada").join("config.toml"),
+                    }
+                } else {
+                    Config::config_file_path()?
+                };
+                Config::set_in_file(&path, &format!("{}={}", key, value))?;
+                println!("Set {} in {}", key, path.display());
+            }
+        },
+        Command::Sessions { action } => match action {
+            SessionsCommand::List => println!("{}", Ada::list_sessions("Run ada sessions show <id> to print one.")),
+            SessionsCommand::Show { id } => println!("{}", Session::load(&id)?.transcript()),
+            SessionsCommand::Delete { id } => println!("Deleted session {}", Session::delete(&id)?),
+        },
+        Command::Tools { action: ToolsCommand::List } => {
+            let (config, _) = Config::load(&overrides)?;
+            let mcp = McpServers::connect(&config.mcp).await;
+            let tools = ToolRegistry::new(&config, Arc::new(Approvals::new(config.permission_mode)), &mcp)?;
+            print!("{}", tools.describe());
+            for (server, error) in mcp.failures() {
+                eprintln!("MCP server '{}' unavailable: {}", server, error);
+            }
+        }
+        Command::Serve { port, host } => {
+            let ada = Ada::new(Session::new(), &overrides).await?;
+            // Every session the server opens starts pinned to --agent
+            if let Some(agent) = &global.agent {
+                ada.pin_agent_for_run(agent).await?;
+            }
+            server::serve(ada, host, port).await?;
+        }
+        Command::Mcp => mcp::serve(&overrides).await?,
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "ada", &mut std::io::stdout());
+        }
+    }
+
+    Ok(true)
+}
@@ -1,7 +1,13 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::agents;
+use crate::approval::PermissionMode;
+use crate::provider::ProviderKind;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -24,6 +30,197 @@ pub struct Config {
     /// Show intent classification in output
     #[serde(default = "default_show_intent")]
     pub show_intent: bool,
+
+    /// When mutating tools need approval: ask, auto-edit or yolo
+    #[serde(default)]
+    pub permission_mode: PermissionMode,
+
+    /// Rule-based pre-routing before the LLM classifier ([routing])
+    #[serde(default)]
+    pub routing: RoutingConfig,
+
+    /// LLM backend ([provider])
+    #[serde(default)]
+    pub provider: ProviderConfig,
+
+    /// Per-agent overrides keyed by agent name ([agents.classifier], [agents.code], ...)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub agents: HashMap<String, AgentConfig>,
+
+    /// Which tools are available and how they are restricted ([tools])
+    #[serde(default)]
+    pub tools: ToolsConfig,
+
+    /// Directory the filesystem tools are confined to ([workspace])
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+
+    /// External MCP servers whose tools agents can use ([mcp.servers.<name>])
+    #[serde(default)]
+    pub mcp: McpConfig,
+}
+
+/// Keyword rules tried before the LLM intent classifier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    /// Try keyword rules before calling the classifier
+    #[serde(default = "default_routing_enabled")]
+    pub enabled: bool,
+
+    /// Minimum rule confidence (0.0-1.0) to skip the classifier
+    #[serde(default = "default_confidence_threshold")]
+    pub confidence_threshold: f32,
+
+    /// Extra regex patterns per agent, added to the built-in ones
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub patterns: HashMap<String, Vec<String>>,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_routing_enabled(),
+            confidence_threshold: default_confidence_threshold(),
+            patterns: HashMap::new(),
+        }
+    }
+}
+
+/// Which backend to use and how to reach it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    /// Backend: openai, anthropic, openai_compatible or local (default: openai)
+    #[serde(default)]
+    pub kind: ProviderKind,
+
+    /// Custom endpoint, e.g. http://localhost:11434/v1 for Ollama
+    pub base_url: Option<String>,
+
+    /// Environment variable holding the API key (default depends on the provider)
+    pub api_key_env: Option<String>,
+}
+
+/// Settings for a single agent. For built-in agents every field is an optional
+/// override; a name that isn't built in declares a new agent (description required).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentConfig {
+    /// What the agent handles; the intent classifier routes on this
+    pub description: Option<String>,
+
+    /// System prompt for the agent
+    pub preamble: Option<String>,
+
+    /// Tools the agent may use, by name (e.g. ["read_file", "grep", "webfetch"])
+    pub tools: Option<Vec<String>>,
+
+    /// Model for this agent (default: top-level model)
+    pub model: Option<String>,
+
+    /// Maximum tokens for this agent's responses (default: top-level max_tokens)
+    pub max_tokens: Option<u32>,
+}
+
+/// Tool availability and per-tool restrictions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolsConfig {
+    /// Only these tools are offered to agents (default: all)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+
+    /// Tools never offered to agents; wins over `allow`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+
+    #[serde(default)]
+    pub webfetch: WebFetchConfig,
+
+    #[serde(default)]
+    pub execute: ExecuteConfig,
+
+    #[serde(default)]
+    pub file_ops: FileOpsConfig,
+}
+
+/// [tools.webfetch]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebFetchConfig {
+    /// Hosts that may be fetched, including their subdomains (default: any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_domains: Vec<String>,
+}
+
+/// [tools.execute]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecuteConfig {
+    /// Programs or command prefixes that are refused (e.g. "rm", "git push")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_commands: Vec<String>,
+}
+
+/// [tools.file_ops]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileOpsConfig {
+    /// Operations that are refused (delete, move, rename, copy)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_operations: Vec<String>,
+}
+
+/// Where the filesystem tools may read and write
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Workspace root (default: the directory Ada is started in)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+
+    /// What happens when a tool touches a path outside the root
+    #[serde(default)]
+    pub outside: OutsideAccess,
+}
+
+/// [mcp]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpConfig {
+    /// Servers by name; their tools are registered as <name>__<tool>
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub servers: BTreeMap<String, McpServerConfig>,
+}
+
+/// One MCP server, started as a subprocess (command) or reached over HTTP (url)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Program speaking MCP on stdin/stdout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+
+    /// Extra environment variables for the command
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Streamable HTTP endpoint, e.g. http://localhost:8080/mcp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Extra HTTP headers, e.g. Authorization
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+
+    /// Agents given every tool of this server (default: general)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<String>>,
+}
+
+/// Handling of paths outside the workspace root
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutsideAccess {
+    /// Refuse the call
+    #[default]
+    Deny,
+    /// Ask for approval, whatever the permission mode
+    Ask,
 }
 
 fn default_model() -> String {
@@ -46,6 +243,14 @@ fn default_show_intent() -> bool {
     true
 }
 
+fn default_routing_enabled() -> bool {
+    true
+}
+
+fn default_confidence_threshold() -> f32 {
+    0.6
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -54,6 +259,13 @@ impl Default for Config {
             multi_turn_depth: default_multi_turn_depth(),
             enable_direct_commands: default_direct_commands(),
             show_intent: default_show_intent(),
+            permission_mode: PermissionMode::default(),
+            routing: RoutingConfig::default(),
+            provider: ProviderConfig::default(),
+            agents: HashMap::new(),
+            tools: ToolsConfig::default(),
+            workspace: WorkspaceConfig::default(),
+            mcp: McpConfig::default(),
         }
     }
 }
@@ -66,13 +278,72 @@ impl Config {
         Ok(PathBuf::from(home).join(".ada"))
     }
 
-    /// Get the config file path (~/.ada/config)
+    /// Get the config file path (~/.ada/config, or the file given with --config)
     pub fn config_file_path() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join("config"))
+        match CONFIG_FILE.get() {
+            Some(path) => Ok(path.clone()),
+            None => Ok(Self::config_dir()?.join("config")),
+        }
+    }
+
+    /// Read the global settings from `path` instead of ~/.ada/config for the rest
+    /// of the process
+    pub fn use_file(path: PathBuf) -> Result<()> {
+        if !path.is_file() {
+            bail!("Config file {} does not exist", path.display());
+        }
+        CONFIG_FILE
+            .set(path)
+            .map_err(|_| anyhow::anyhow!("The config file was already chosen"))
+    }
+
+    /// Model to use for the named agent
+    pub fn model_for(&self, agent: &str) -> &str {
+        self.agent_sections(agent)
+            .into_iter()
+            .rev()
+            .find_map(|a| a.model.as_deref())
+            .unwrap_or(&self.model)
+    }
+
+    /// Token limit to use for the named agent
+    pub fn max_tokens_for(&self, agent: &str) -> u32 {
+        self.agent_sections(agent)
+            .into_iter()
+            .rev()
+            .find_map(|a| a.max_tokens)
+            .unwrap_or(self.max_tokens)
     }
 
-    /// Load configuration from ~/.ada/config or create default if not exists
-    pub fn load() -> Result<Self> {
+    /// [agents.*] sections that apply to an agent, under its name or its built-in
+    /// alias, in the order agent_specs applies them (later wins)
+    fn agent_sections(&self, agent: &str) -> Vec<&AgentConfig> {
+        let alias = agents::alias_of(agent);
+        let mut sections: Vec<(&String, &AgentConfig)> = self
+            .agents
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(agent) || alias.is_some_and(|a| key.eq_ignore_ascii_case(a)))
+            .collect();
+        sections.sort_by(|a, b| a.0.cmp(b.0));
+        sections.into_iter().map(|(_, section)| section).collect()
+    }
+
+    /// Project config file (.ada/config.toml), found by walking up from the current directory
+    pub fn project_config_path() -> Option<PathBuf> {
+        let cwd = std::env::current_dir().ok()?;
+        let global_dir = Self::config_dir().ok();
+        cwd.ancestors()
+            .map(|dir| dir.join(".ada"))
+            // ~/.ada holds the global config and sessions, not a project config
+            .filter(|dir| Some(dir) != global_dir.as_ref())
+            .map(|dir| dir.join("config.toml"))
+            .find(|path| path.is_file())
+    }
+
+    /// Load the effective configuration. Later layers win: built-in defaults,
+    /// ~/.ada/config, the project's .ada/config.toml, ADA_* environment variables,
+    /// then `overrides` from the command line ("key=value", dotted keys for sections).
+    pub fn load(overrides: &[String]) -> Result<(Self, ConfigSources)> {
         let config_dir = Self::config_dir()?;
         let config_file = Self::config_file_path()?;
 
@@ -83,23 +354,220 @@ impl Config {
             eprintln!("Created config directory: {}", config_dir.display());
         }
 
-        // If config file exists, read it
-        if config_file.exists() {
-            let contents = fs::read_to_string(&config_file)
-                .context("Failed to read config file")?;
+        // Create the global config file with defaults on first run
+        if !config_file.exists() && CONFIG_FILE.get().is_none() {
+            Config::default().save()?;
+            eprintln!("Created default config at: {}", config_file.display());
+        }
 
-            let config: Config = toml::from_str(&contents)
-                .context("Failed to parse config file")?;
+        let mut merged = toml::Table::new();
+        let mut sources = ConfigSources::default();
 
-            eprintln!("Loaded config from: {}", config_file.display());
-            Ok(config)
-        } else {
-            // Create default config file
-            let default_config = Config::default();
-            default_config.save()?;
-            eprintln!("Created default config at: {}", config_file.display());
-            Ok(default_config)
+        let mut files = vec![(ConfigLayer::Global, config_file)];
+        files.extend(Self::project_config_path().map(|path| (ConfigLayer::Project, path)));
+        for (layer, path) in files {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let table: toml::Table = toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            sources.merge(&mut merged, table, layer);
+            sources.files.push((layer, path));
+        }
+
+        // ADA_* variables that aren't settings (ADA_LOG, ADA_HOME, ...) belong to
+        // something else and are skipped rather than reported
+        let env: Vec<String> = std::env::vars()
+            .filter_map(|(name, value)| {
+                let key = name.strip_prefix("ADA_")?.to_lowercase().replace("__", ".");
+                Some(format!("{}={}", key, value))
+            })
+            .filter(|assignment| is_setting(assignment))
+            .collect();
+        sources.merge(&mut merged, assignments(&env, "ADA_*")?, ConfigLayer::Env);
+        sources.merge(&mut merged, assignments(overrides, "--set")?, ConfigLayer::Cli);
+        sources.overrides = overrides.to_vec();
+
+        // Keys serde would silently skip are usually typos, so they are reported
+        let mut unknown = Vec::new();
+        let config: Config = serde_ignored::deserialize(toml::Value::Table(merged), |path| {
+            unknown.push(path.to_string())
+        })
+        .context("Invalid configuration")?;
+
+        let mut problems: Vec<ConfigProblem> = unknown
+            .into_iter()
+            .map(|key| ConfigProblem::new(&key, "unknown setting"))
+            .collect();
+        problems.extend(config.validate());
+        if !problems.is_empty() {
+            let lines: Vec<String> = problems
+                .iter()
+                .map(|problem| format!("  {} ({}): {}", problem.key, sources.origin(&problem.key), problem.message))
+                .collect();
+            bail!("Invalid configuration:\n{}", lines.join("\n"));
+        }
+
+        Ok((config, sources))
+    }
+
+    /// Values that parse but can't be used
+    fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+
+        if !(1..=MAX_MULTI_TURN_DEPTH).contains(&self.multi_turn_depth) {
+            problems.push(ConfigProblem::new(
+                "multi_turn_depth",
+                format!("must be between 1 and {}, got {}", MAX_MULTI_TURN_DEPTH, self.multi_turn_depth),
+            ));
+        }
+        if self.max_tokens == 0 {
+            problems.push(ConfigProblem::new("max_tokens", "must be greater than 0"));
+        }
+        if !(0.0..=1.0).contains(&self.routing.confidence_threshold) {
+            problems.push(ConfigProblem::new(
+                "routing.confidence_threshold",
+                format!("must be between 0.0 and 1.0, got {}", self.routing.confidence_threshold),
+            ));
+        }
+
+        for (name, server) in &self.mcp.servers {
+            let key = format!("mcp.servers.{}", name);
+            // Server names become part of tool names, which providers restrict
+            if name.is_empty() || name.contains("__") || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                problems.push(ConfigProblem::new(&key, "server names may only use letters, digits, '-' and single '_'"));
+            }
+            match (&server.command, &server.url) {
+                (Some(_), Some(_)) => problems.push(ConfigProblem::new(&key, "set either command or url, not both")),
+                (None, None) => problems.push(ConfigProblem::new(&key, "needs a command or a url")),
+                (None, Some(url)) if !url.starts_with("http://") && !url.starts_with("https://") => {
+                    problems.push(ConfigProblem::new(&format!("{}.url", key), format!("'{}' is not an http(s) URL", url)));
+                }
+                _ => {}
+            }
+        }
+
+        let mut models = vec![("model".to_string(), self.model.as_str())];
+        for (name, agent) in &self.agents {
+            if let Some(model) = &agent.model {
+                models.push((format!("agents.{}.model", name), model));
+            }
+            if agent.max_tokens == Some(0) {
+                problems.push(ConfigProblem::new(&format!("agents.{}.max_tokens", name), "must be greater than 0"));
+            }
         }
+        // Custom endpoints serve whatever models they like, so only the hosted APIs are checked
+        if self.provider.base_url.is_none() {
+            let kind = self.provider.kind;
+            for (key, model) in models {
+                if !kind.recognizes_model(model) {
+                    problems.push(ConfigProblem::new(
+                        &key,
+                        format!("'{}' is not a known {} model ({})", model, kind, kind.model_hint()),
+                    ));
+                }
+            }
+        }
+
+        problems.sort_by(|a, b| a.key.cmp(&b.key));
+        problems
+    }
+
+    /// Settings that differ from `self` in `newer`, as "key → value"
+    pub fn changes(&self, newer: &Config) -> Vec<String> {
+        let leaves = |config: &Config| {
+            let mut values = Vec::new();
+            if let Ok(value) = toml::Value::try_from(config) {
+                flatten("", &value, &mut values);
+            }
+            values.into_iter().collect::<BTreeMap<String, String>>()
+        };
+        let (old, new) = (leaves(self), leaves(newer));
+
+        let mut changes: Vec<String> = new
+            .iter()
+            .filter(|(key, value)| old.get(*key) != Some(value))
+            .map(|(key, value)| {
+                // Preambles and other long strings are cut short
+                let value = value.trim_matches('"');
+                match value.char_indices().nth(40) {
+                    Some((end, _)) => format!("{} → {}…", key, &value[..end]),
+                    None => format!("{} → {}", key, value),
+                }
+            })
+            .collect();
+        changes.extend(
+            old.keys()
+                .filter(|key| !new.contains_key(*key))
+                .map(|key| format!("{} → (unset)", key)),
+        );
+        changes
+    }
+
+    /// Effective value of every setting and the layer it came from
+    pub fn describe(&self, sources: &ConfigSources) -> String {
+        let mut values = Vec::new();
+        if let Ok(toml::Value::Table(table)) = toml::Value::try_from(self) {
+            flatten("", &toml::Value::Table(table), &mut values);
+        }
+
+        let mut output = String::from("Effective configuration:\n");
+        for (key, value) in values {
+            output.push_str(&format!("  {} = {}  [{}]\n", key, value, sources.layer_of(&key)));
+        }
+
+        output.push_str("\nLayers (later wins): default, global, project, env (ADA_*), cli (--set)\n");
+        for (layer, path) in &sources.files {
+            output.push_str(&format!("  {}: {}\n", layer, path.display()));
+        }
+        output.trim_end().to_string()
+    }
+
+    /// Effective value of a dotted key; a section prints every setting under it
+    pub fn get(&self, key: &str) -> Option<String> {
+        let mut value = toml::Value::try_from(self).ok()?;
+        for part in key.split('.') {
+            value = value.as_table_mut()?.remove(part)?;
+        }
+
+        match value {
+            toml::Value::Table(_) => {
+                let mut values = Vec::new();
+                flatten(key, &value, &mut values);
+                let lines: Vec<String> = values.iter().map(|(key, value)| format!("{} = {}", key, value)).collect();
+                Some(lines.join("\n"))
+            }
+            toml::Value::String(text) => Some(text),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// Write "key=value" into a config file, keeping its other settings. The file
+    /// is restored if the result doesn't load.
+    pub fn set_in_file(path: &Path, assignment: &str) -> Result<()> {
+        let original = match fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let mut table: toml::Table = toml::from_str(original.as_deref().unwrap_or_default())
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        ConfigSources::default().merge(&mut table, assignments(&[assignment.to_string()], "config set")?, ConfigLayer::Cli);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let contents = toml::to_string_pretty(&table).context("Failed to serialize config")?;
+        fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        if let Err(e) = Self::load(&[]) {
+            let restored = match original {
+                Some(contents) => fs::write(path, contents),
+                None => fs::remove_file(path),
+            };
+            restored.with_context(|| format!("Failed to restore {}", path.display()))?;
+            return Err(e);
+        }
+        Ok(())
     }
 
     /// Save configuration to ~/.ada/config
@@ -114,3 +582,178 @@ impl Config {
         Ok(())
     }
 }
+
+/// Global config file chosen with --config
+static CONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Upper bound for multi_turn_depth; deeper loops are almost always a runaway agent
+const MAX_MULTI_TURN_DEPTH: usize = 100;
+
+/// A setting that failed validation
+#[derive(Debug, Clone)]
+struct ConfigProblem {
+    /// Dotted key, e.g. agents.code.model
+    key: String,
+    message: String,
+}
+
+impl ConfigProblem {
+    fn new(key: &str, message: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Where a setting came from, lowest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLayer {
+    Default,
+    Global,
+    Project,
+    Env,
+    Cli,
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ConfigLayer::Default => "default",
+            ConfigLayer::Global => "global",
+            ConfigLayer::Project => "project",
+            ConfigLayer::Env => "env",
+            ConfigLayer::Cli => "cli",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Which layer set each setting
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    /// Dotted key -> layer that set it last
+    layers: BTreeMap<String, ConfigLayer>,
+    /// Config files that were read, in order
+    pub files: Vec<(ConfigLayer, PathBuf)>,
+    /// Command-line overrides, kept so a reload applies them again
+    pub overrides: Vec<String>,
+}
+
+impl ConfigSources {
+    /// Layer of a dotted key, or of the closest section that was set as a whole
+    pub fn layer_of(&self, key: &str) -> ConfigLayer {
+        let mut key = key;
+        loop {
+            if let Some(layer) = self.layers.get(key) {
+                return *layer;
+            }
+            match key.rsplit_once('.') {
+                Some((parent, _)) => key = parent,
+                None => return ConfigLayer::Default,
+            }
+        }
+    }
+
+    /// Where a key was set, for error messages: the file, ADA_* or --set
+    pub fn origin(&self, key: &str) -> String {
+        match self.layer_of(key) {
+            ConfigLayer::Default => "default".to_string(),
+            ConfigLayer::Env => "ADA_* environment variable".to_string(),
+            ConfigLayer::Cli => "--set".to_string(),
+            layer => self
+                .files
+                .iter()
+                .rev()
+                .find(|(file_layer, _)| *file_layer == layer)
+                .map(|(_, path)| path.display().to_string())
+                .unwrap_or_else(|| layer.to_string()),
+        }
+    }
+
+    /// Merge `from` into `into`, recording the layer for every value it sets.
+    /// Tables merge key by key; anything else replaces the earlier value.
+    fn merge(&mut self, into: &mut toml::Table, from: toml::Table, layer: ConfigLayer) {
+        self.merge_at("", into, from, layer);
+    }
+
+    fn merge_at(&mut self, prefix: &str, into: &mut toml::Table, from: toml::Table, layer: ConfigLayer) {
+        for (key, value) in from {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            let value = match (into.get_mut(&key), value) {
+                (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => {
+                    self.merge_at(&path, existing, table, layer);
+                    continue;
+                }
+                (None, toml::Value::Table(table)) => {
+                    // Record each value of a new section rather than the section as a whole
+                    let mut section = toml::Table::new();
+                    self.merge_at(&path, &mut section, table, layer);
+                    into.insert(key, toml::Value::Table(section));
+                    continue;
+                }
+                (_, value) => value,
+            };
+
+            // Everything under a replaced key now comes from this layer
+            let nested = format!("{}.", path);
+            self.layers.retain(|k, _| !k.starts_with(&nested));
+            self.layers.insert(path, layer);
+            into.insert(key, value);
+        }
+    }
+}
+
+/// Build a table from "dotted.key=value" assignments. Values are parsed as TOML
+/// (numbers, booleans, arrays) and fall back to plain strings.
+fn assignments(entries: &[String], origin: &str) -> Result<toml::Table> {
+    let mut table = toml::Table::new();
+    for entry in entries {
+        let Some((key, raw)) = entry.split_once('=') else {
+            bail!("{}: expected key=value, got '{}'", origin, entry);
+        };
+        let value = toml::from_str::<toml::Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut t| t.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+        let mut parts: Vec<&str> = key.trim().split('.').collect();
+        let last = parts.pop().unwrap_or_default();
+        let mut target = &mut table;
+        for part in parts {
+            let entry = target
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            target = match entry {
+                toml::Value::Table(inner) => inner,
+                _ => bail!("{}: '{}' is not a section", origin, part),
+            };
+        }
+        target.insert(last.to_string(), value);
+    }
+    Ok(table)
+}
+
+/// Whether a "key=value" assignment names a setting. Values of the wrong type still
+/// count, so they are reported when the config is loaded.
+fn is_setting(assignment: &str) -> bool {
+    let Ok(table) = assignments(&[assignment.to_string()], "ADA_*") else {
+        return false;
+    };
+    let mut known = true;
+    let _: Result<Config, _> = serde_ignored::deserialize(toml::Value::Table(table), |_| known = false);
+    known
+}
+
+/// Dotted key/value pairs for every leaf of a TOML value
+fn flatten(prefix: &str, value: &toml::Value, out: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, value, out);
+            }
+        }
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
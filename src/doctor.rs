@@ -0,0 +1,159 @@
+use std::io::IsTerminal;
+use std::process::Command;
+
+use crate::Ada;
+use crate::config::Config;
+use crate::provider::Provider;
+use crate::tools::McpServers;
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Status::Ok => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        }
+    }
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Check the environment Ada needs and print a report. Returns false if anything
+/// would stop Ada from running.
+pub async fn run(overrides: &[String]) -> bool {
+    let mut checks = Vec::new();
+
+    match Config::load(overrides) {
+        Ok((config, sources)) => {
+            let files: Vec<String> = sources.files.iter().map(|(_, path)| path.display().to_string()).collect();
+            checks.push(Check::new("config", Status::Ok, format!("valid ({})", files.join(", "))));
+            checks.push(provider_check(&config));
+            if !config.mcp.servers.is_empty() {
+                checks.push(mcp_check(&config).await);
+            }
+        }
+        Err(e) => {
+            checks.push(Check::new("config", Status::Fail, format!("{:#}", e)));
+            checks.push(Check::new("provider", Status::Warn, "skipped until the config is valid"));
+        }
+    }
+    checks.push(path_check());
+    checks.push(git_check());
+    checks.push(terminal_check());
+
+    println!("ada doctor\n");
+    for check in &checks {
+        let mut lines = check.detail.lines();
+        println!("{} {:<10} {}", check.status.symbol(), check.name, lines.next().unwrap_or_default());
+        for line in lines {
+            println!("  {:<10} {}", "", line);
+        }
+    }
+
+    let failures = checks.iter().filter(|check| check.status == Status::Fail).count();
+    let warnings = checks.iter().filter(|check| check.status == Status::Warn).count();
+    println!("\n{} failed, {} warning(s)", failures, warnings);
+    failures == 0
+}
+
+/// Credentials and endpoint, checked by building the client the agents would use
+fn provider_check(config: &Config) -> Check {
+    let kind = config.provider.kind;
+    match Provider::from_config(config) {
+        Ok(_) => {
+            let endpoint = config.provider.base_url.as_deref().unwrap_or("default endpoint");
+            Check::new("provider", Status::Ok, format!("{} ({}), model {}", kind, endpoint, config.model))
+        }
+        Err(e) => Check::new("provider", Status::Fail, format!("{:#}", e)),
+    }
+}
+
+/// Every MCP server should start and list its tools
+async fn mcp_check(config: &Config) -> Check {
+    let servers = McpServers::connect(&config.mcp).await;
+    let lines: Vec<String> = config
+        .mcp
+        .servers
+        .keys()
+        .map(|name| match servers.failures().iter().find(|(server, _)| server == name) {
+            Some((_, error)) => format!("{}: {}", name, error),
+            None => format!("{}: {} tools", name, servers.tool_names(name).len()),
+        })
+        .collect();
+    let status = if servers.failures().is_empty() { Status::Ok } else { Status::Warn };
+    Check::new("mcp", status, lines.join("\n"))
+}
+
+/// Direct commands only work for programs found on PATH
+fn path_check() -> Check {
+    if std::env::var_os("PATH").is_none() {
+        return Check::new("PATH", Status::Fail, "PATH is not set; direct commands won't be recognized");
+    }
+    match Ada::load_path_commands().len() {
+        0 => Check::new("PATH", Status::Warn, "no executables found; direct commands won't be recognized"),
+        count => Check::new("PATH", Status::Ok, format!("{} commands found", count)),
+    }
+}
+
+/// The git tool and the git agent run the git binary
+fn git_check() -> Check {
+    match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let repo = match git2::Repository::discover(".") {
+                Ok(_) => "inside a repository",
+                Err(_) => "not inside a repository",
+            };
+            Check::new("git", Status::Ok, format!("{}, {}", version, repo))
+        }
+        _ => Check::new("git", Status::Fail, "git not found on PATH; the git tool and git agent won't work"),
+    }
+}
+
+/// The TUI needs an interactive terminal with a usable size
+fn terminal_check() -> Check {
+    if !std::io::stdout().is_terminal() {
+        return Check::new("terminal", Status::Warn, "stdout is not a terminal; the TUI needs one");
+    }
+    let term = std::env::var("TERM").unwrap_or_default();
+    if term == "dumb" {
+        return Check::new("terminal", Status::Warn, "TERM=dumb; the TUI needs cursor movement");
+    }
+
+    let size = match crossterm::terminal::size() {
+        Ok((columns, rows)) => format!("{}x{}", columns, rows),
+        Err(_) => "unknown size".to_string(),
+    };
+    let colors = if std::env::var_os("NO_COLOR").is_some() {
+        "colors disabled (NO_COLOR)"
+    } else if matches!(std::env::var("COLORTERM").as_deref(), Ok("truecolor") | Ok("24bit")) {
+        "truecolor"
+    } else if term.contains("256color") {
+        "256 colors"
+    } else {
+        "basic colors"
+    };
+    Check::new("terminal", Status::Ok, format!("{} ({}), {}", term, size, colors))
+}
@@ -0,0 +1,118 @@
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+/// Progress of a request, emitted while it runs so the UI can show it live
+#[derive(Debug, Clone)]
+pub enum AgentEvent {
+    /// The request was routed to an agent; `via` says whether rules or the classifier decided
+    Routed { intent: String, agent: String, via: String },
+    /// A chunk of assistant text
+    Text(String),
+    /// The model asked for a tool; it is now running
+    ToolCall { name: String, arguments: Value },
+    /// A tool finished
+    ToolResult { name: String, result: String },
+    /// A mutating tool call is waiting for the user; answer with `Approvals::resolve`
+    ApprovalRequested {
+        id: u64,
+        tool: String,
+        arguments: Value,
+        /// What the call would change, e.g. a diff
+        preview: Option<String>,
+    },
+    /// Tokens used by an agent run, reported when it finishes
+    Usage { input_tokens: u64, output_tokens: u64 },
+}
+
+impl AgentEvent {
+    /// JSON form, tagged by "type", for stream-json output and the HTTP API
+    pub fn to_json(&self) -> Value {
+        match self {
+            AgentEvent::Routed { intent, agent, via } => {
+                json!({ "type": "routed", "intent": intent, "agent": agent, "via": via })
+            }
+            AgentEvent::Text(text) => json!({ "type": "text", "text": text }),
+            AgentEvent::ToolCall { name, arguments } => {
+                json!({ "type": "tool_call", "name": name, "arguments": arguments })
+            }
+            AgentEvent::ToolResult { name, result } => {
+                json!({ "type": "tool_result", "name": name, "result": decode_result(result) })
+            }
+            AgentEvent::ApprovalRequested { id, tool, arguments, preview } => json!({
+                "type": "approval_requested",
+                "id": id,
+                "tool": tool,
+                "arguments": arguments,
+                "preview": preview,
+            }),
+            AgentEvent::Usage { input_tokens, output_tokens } => {
+                json!({ "type": "usage", "input_tokens": input_tokens, "output_tokens": output_tokens })
+            }
+        }
+    }
+}
+
+/// Tool outputs arrive JSON-encoded; keep structured values structured
+pub fn decode_result(result: &str) -> Value {
+    serde_json::from_str(result).unwrap_or_else(|_| Value::String(result.to_string()))
+}
+
+pub type EventSender = mpsc::UnboundedSender<AgentEvent>;
+pub type EventReceiver = mpsc::UnboundedReceiver<AgentEvent>;
+
+pub fn channel() -> (EventSender, EventReceiver) {
+    mpsc::unbounded_channel()
+}
+
+/// Header line for a tool call, e.g. "⏺ grep(TODO)"
+pub fn tool_call_label(name: &str, arguments: &Value) -> String {
+    // The argument that says what the call is about; other tools (MCP) show their
+    // first string argument
+    let key = match name {
+        "grep" | "glob" => "/pattern",
+        "execute" => "/command",
+        "read_file" | "edit" => "/file_path",
+        "write_files" => "/files/0/path",
+        "webfetch" => "/url",
+        "git" => "/operation",
+        "file_ops" => "/source",
+        "search_directory" => "/directory",
+        "list_directory" | "tree" => "/path",
+        _ => "",
+    };
+    let summary = arguments
+        .pointer(key)
+        .and_then(Value::as_str)
+        .or_else(|| arguments.as_object()?.values().find_map(|v| v.as_str()))
+        .unwrap_or_default();
+
+    let summary = if summary.chars().count() > 60 {
+        format!("{}…", summary.chars().take(60).collect::<String>())
+    } else {
+        summary.to_string()
+    };
+
+    format!("⏺ {}({})", name, summary.replace('\n', " "))
+}
+
+/// Marker appended to a tool call label while the tool is running
+pub const RUNNING_MARKER: &str = " …running";
+
+/// Short preview of a tool result shown under its call label
+pub fn tool_result_preview(result: &str) -> String {
+    // Tool outputs arrive JSON-encoded; show the plain text when possible
+    let text = serde_json::from_str::<String>(result).unwrap_or_else(|_| result.to_string());
+
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let first = lines.next().unwrap_or("(no output)").trim();
+    let first = if first.chars().count() > 100 {
+        format!("{}…", first.chars().take(100).collect::<String>())
+    } else {
+        first.to_string()
+    };
+
+    match lines.count() {
+        0 => format!("  ⎿  {}", first),
+        more => format!("  ⎿  {} (+{} more lines)", first, more),
+    }
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::io::{IsTerminal, Read};
+use std::sync::Arc;
+
+use crate::approval::ApprovalDecision;
+use crate::cli::OutputFormat;
+use crate::events::{self, AgentEvent};
+use crate::provider::{RunOutcome, RunResult};
+use crate::{ActiveRequest, Ada};
+
+/// The request completed
+pub const EXIT_OK: i32 = 0;
+/// The agent couldn't answer: provider error, unknown agent, failed plan
+pub const EXIT_FAILED: i32 = 1;
+/// The agent answered, but at least one tool call failed or was denied
+pub const EXIT_TOOL_FAILED: i32 = 3;
+/// Interrupted with Ctrl+C
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Piped input beyond this many bytes is cut off
+const MAX_STDIN_BYTES: usize = 100_000;
+
+/// The prompt plus whatever was piped to stdin, e.g. `cargo test 2>&1 | ada -p "why is this failing"`
+pub fn prompt_with_stdin(prompt: String) -> std::io::Result<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(prompt);
+    }
+
+    let mut input = String::new();
+    stdin.lock().read_to_string(&mut input)?;
+    let input = input.trim_end();
+    if input.is_empty() {
+        return Ok(prompt);
+    }
+
+    let (input, note) = match input.char_indices().find(|(i, _)| *i >= MAX_STDIN_BYTES) {
+        Some((end, _)) => (&input[..end], format!(" (first {} bytes)", end)),
+        None => (input, String::new()),
+    };
+    Ok(format!("{}\n\nInput piped to stdin{}:\n```\n{}\n```", prompt, note, input))
+}
+
+/// A tool call the agent made, with its result once it finished
+#[derive(Debug, Serialize)]
+struct ToolCallRecord {
+    name: String,
+    arguments: Value,
+    result: Option<Value>,
+    /// Needed approval that nobody could give
+    denied: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+struct Usage {
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// Everything a run produced; the --output json result
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    /// completed, failed or interrupted
+    status: &'static str,
+    exit_code: i32,
+    /// Pass to --resume to continue the conversation
+    session_id: String,
+    intent: Option<String>,
+    agent: Option<String>,
+    /// What made the routing decision: rules, classifier, override, ...
+    via: Option<String>,
+    answer: String,
+    error: Option<String>,
+    tool_calls: Vec<ToolCallRecord>,
+    usage: Usage,
+}
+
+/// Last line of --output stream-json
+#[derive(Serialize)]
+struct ResultLine<'a> {
+    r#type: &'static str,
+    #[serde(flatten)]
+    report: &'a Report,
+}
+
+/// Run one request without the TUI and return the process exit code. In text mode
+/// the agent's reply goes to stdout and routing, tool activity and errors go to
+/// stderr. Nobody is around to approve mutating tool calls, so they are denied
+/// unless the permission mode lets them through.
+pub async fn run(ada: Arc<Ada>, prompt: String, format: OutputFormat) -> i32 {
+    let streaming = format == OutputFormat::StreamJson;
+    let failed_before = ada.tools.failed_calls();
+    let mut report = Report::default();
+    let mut text = String::new();
+    // Text after a tool call or a new route comes from another model turn
+    let mut turn_ended = false;
+
+    let mut request = ActiveRequest::start(Arc::clone(&ada), prompt);
+    let result = loop {
+        tokio::select! {
+            // Events first, so none are left behind when the task finishes
+            biased;
+            Some(event) = request.events.recv() => {
+                // Denials are reported as such below, not as requests
+                if streaming && !matches!(event, AgentEvent::ApprovalRequested { .. }) {
+                    println!("{}", event.to_json());
+                }
+                match event {
+                    AgentEvent::Routed { intent, agent, via } => {
+                        if format == OutputFormat::Text {
+                            eprintln!("→ {} ({})", agent, via);
+                        }
+                        turn_ended = true;
+                        report.intent = Some(intent);
+                        report.agent = Some(agent);
+                        report.via = Some(via);
+                    }
+                    AgentEvent::Text(chunk) => {
+                        if std::mem::take(&mut turn_ended) && !text.is_empty() && !text.ends_with('\n') {
+                            text.push('\n');
+                        }
+                        text.push_str(&chunk);
+                    }
+                    AgentEvent::ToolCall { name, arguments } => {
+                        turn_ended = true;
+                        if format == OutputFormat::Text {
+                            eprintln!("⚙ {}", name);
+                        }
+                        report.tool_calls.push(ToolCallRecord { name, arguments, result: None, denied: false });
+                    }
+                    AgentEvent::ToolResult { name, result } => {
+                        // Results come back in call order
+                        let pending = report.tool_calls.iter_mut().find(|call| call.name == name && call.result.is_none());
+                        if let Some(call) = pending {
+                            call.result = Some(events::decode_result(&result));
+                        }
+                    }
+                    AgentEvent::ApprovalRequested { id, tool, arguments, .. } => {
+                        if streaming {
+                            println!("{}", json!({ "type": "approval_denied", "tool": tool, "arguments": arguments }));
+                        } else if format == OutputFormat::Text {
+                            eprintln!("✗ {} needs approval; run interactively or use --set permission_mode=yolo", tool);
+                        }
+                        // The call event arrives before its approval request
+                        let pending = report.tool_calls.iter_mut().rev().find(|call| call.name == tool && call.result.is_none());
+                        if let Some(call) = pending {
+                            call.denied = true;
+                        }
+                        ada.tools.approvals().resolve(
+                            id,
+                            ApprovalDecision::Deny("Running non-interactively; nobody can approve this call".to_string()),
+                        );
+                    }
+                    AgentEvent::Usage { input_tokens, output_tokens } => {
+                        report.usage.input_tokens += input_tokens;
+                        report.usage.output_tokens += output_tokens;
+                    }
+                }
+            }
+            _ = tokio::signal::ctrl_c() => request.cancel.cancel(),
+            result = &mut request.task => break result,
+        }
+    };
+
+    let result = result.unwrap_or_else(|e| RunResult::failed(format!("Error: agent task failed: {}", e)));
+    // Streamed text is the bare answer; commands that don't stream (direct commands,
+    // /help) only have the display
+    report.answer = if text.trim().is_empty() { result.display.trim().to_string() } else { text.trim().to_string() };
+    report.session_id = ada.session_snapshot().await.0;
+    let tools_failed = report.tool_calls.iter().any(|call| call.denied) || ada.tools.failed_calls() > failed_before;
+    report.status = result.outcome.status();
+    report.exit_code = match &result.outcome {
+        RunOutcome::Completed if tools_failed => EXIT_TOOL_FAILED,
+        RunOutcome::Completed => EXIT_OK,
+        RunOutcome::Interrupted => EXIT_INTERRUPTED,
+        RunOutcome::Failed(e) => {
+            report.error = Some(e.clone());
+            EXIT_FAILED
+        }
+    };
+
+    match format {
+        OutputFormat::Text if result.outcome == RunOutcome::Completed => println!("{}", report.answer),
+        OutputFormat::Text => eprintln!("{}", result.display.trim()),
+        OutputFormat::Json => match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to serialize the result: {}", e),
+        },
+        OutputFormat::StreamJson => match serde_json::to_string(&ResultLine { r#type: "result", report: &report }) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("Failed to serialize the result: {}", e),
+        },
+    }
+    report.exit_code
+}
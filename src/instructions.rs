@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::config::Config;
+
+/// Project notes (build commands, code style, off-limits directories) appended to
+/// every specialist's preamble
+#[derive(Debug, Clone, Default)]
+pub struct Instructions {
+    /// Every candidate file with its modification time when loaded (None if missing)
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    text: String,
+}
+
+impl Instructions {
+    /// Candidate files, general to specific: ~/.ada/ADA.md, ~/.ada/instructions.md,
+    /// then ADA.md and .ada/instructions.md in the workspace root
+    fn candidates(workspace: &Path) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Ok(dir) = Config::config_dir() {
+            paths.push(dir.join("ADA.md"));
+            paths.push(dir.join("instructions.md"));
+        }
+        paths.push(workspace.join("ADA.md"));
+        paths.push(workspace.join(".ada").join("instructions.md"));
+        paths
+    }
+
+    pub fn load(workspace: &Path) -> Self {
+        let mut files = Vec::new();
+        let mut sections = Vec::new();
+
+        for path in Self::candidates(workspace) {
+            files.push((path.clone(), modified(&path)));
+
+            // Unreadable files are skipped; they'll be retried when they change
+            let Ok(content) = fs::read_to_string(&path) else { continue };
+            if !content.trim().is_empty() {
+                sections.push(format!("Instructions from {}:\n{}", path.display(), content.trim()));
+            }
+        }
+
+        Self {
+            files,
+            text: sections.join("\n\n"),
+        }
+    }
+
+    /// Paths of the instruction files that exist
+    pub fn sources(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, modified)| modified.is_some())
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// Every candidate path, whether or not it exists
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Whether any file was created, changed or removed since loading
+    pub fn is_stale(&self) -> bool {
+        self.files.iter().any(|(path, loaded)| modified(path) != *loaded)
+    }
+
+    /// Append the instructions to a preamble
+    pub fn apply(&self, preamble: &str) -> String {
+        if self.text.is_empty() {
+            preamble.to_string()
+        } else {
+            format!("{}\n\n{}", preamble, self.text)
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
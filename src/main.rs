@@ -1,117 +1,209 @@
 mod tools;
 mod ui;
+mod agents;
+mod approval;
+mod cli;
 mod config;
-
+mod doctor;
+mod events;
+mod headless;
+mod instructions;
+mod mcp;
+mod planner;
+mod provider;
+mod router;
+mod server;
+mod session;
+mod watcher;
+
+use agents::{AgentRegistry, Specialist};
 use anyhow::Result;
-use config::Config;
-use rig::agent::Agent;
-use rig::completion::Prompt;
-use rig::prelude::*;
-use rig::providers::openai;
-use rig::providers::openai::responses_api::ResponsesCompletionModel;
+use approval::Approvals;
+use clap::Parser;
+use cli::Cli;
+use config::{Config, ConfigSources};
+use events::{AgentEvent, EventSender};
+use instructions::Instructions;
+use provider::{AnyAgent, Provider, RunOutcome, RunResult};
+use router::{RouteDecision, RouteSource, Router};
+use rig::completion::Message;
+use session::{Session, SessionEntry};
 use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tools::*;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tools::{McpServers, ToolRegistry};
 use ui::{App, MessageRole, UI};
+use watcher::ConfigWatcher;
 
 struct Ada {
     config: Config,
-    intent_classifier: Agent<ResponsesCompletionModel>,
-    code_agent: Agent<ResponsesCompletionModel>,
-    file_agent: Agent<ResponsesCompletionModel>,
-    git_agent: Agent<ResponsesCompletionModel>,
-    execute_agent: Agent<ResponsesCompletionModel>,
-    web_agent: Agent<ResponsesCompletionModel>,
-    general_agent: Agent<ResponsesCompletionModel>,
-    available_commands: HashSet<String>,
+    /// Which layer each setting came from, for /config
+    config_sources: ConfigSources,
+    intent_classifier: AnyAgent,
+    /// Built-in and user-defined specialists, including the planner
+    agents: AgentRegistry,
+    /// Every tool an agent can be given
+    tools: ToolRegistry,
+    /// ADA.md and .ada/instructions.md, appended to every specialist's preamble
+    instructions: Instructions,
+    available_commands: Arc<HashSet<String>>,
+    /// Keyword rules tried before the LLM classifier
+    router: Router,
+    /// Current session: transcript plus the history shared by every specialist agent.
+    /// Shared with rebuilt instances so a reload keeps the conversation.
+    session: Arc<Mutex<Session>>,
 }
 
 impl Ada {
-    fn new() -> Self {
-        // Load configuration from ~/.ada/config
-        let config = Config::load().expect("Failed to load configuration");
+    async fn new(session: Session, overrides: &[String]) -> Result<Self> {
+        // Global ~/.ada/config, then the project's .ada/config.toml, ADA_* and --set
+        let (config, config_sources) = Config::load(overrides)?;
 
         // Load all available commands from $PATH at startup
         let available_commands = Self::load_path_commands();
         eprintln!("Loaded {} commands from PATH", available_commands.len());
 
-        let client = openai::Client::from_env();
-
-        // Intent classifier - determines which specialized agent to use
-        let intent_classifier = client
-            .agent(openai::GPT_4)
-            .preamble("You are an intent classifier. Analyze the user's request and classify it into ONE of these categories:
-- code_search: searching code, finding functions/classes, grepping content, using regex
-- file_ops: reading, editing, writing, moving, copying, deleting files, listing directories, showing file trees
-- git: git operations like status, diff, log, commit, branch operations
-- execution: running shell commands, executing scripts
-- web: fetching web content, downloading from URLs
-- general: general questions, help, or requests that don't fit above categories
-
-Respond with ONLY the category name, nothing else.")
-            .build();
-
-        // Code search specialist
-        let code_agent = client
-            .agent(openai::GPT_4)
-            .preamble("You are a code search specialist. Help users find and analyze code using grep, glob patterns, and search tools. When tools return formatted output, preserve it exactly.")
-            .tool(Grep)
-            .tool(Glob)
-            .tool(SearchDirectory)
-            .tool(ReadFile)
-            .build();
-
-        // File operations specialist
-        let file_agent = client
-            .agent(openai::GPT_4)
-            .preamble("You are a file operations specialist. Help users read, edit, write, and manage files. When tools return formatted output (especially diffs with ⏺ symbols), ALWAYS include the complete tool output in your response without summarizing. Preserve all formatting, line numbers, and diff markers exactly as returned.")
-            .tool(ReadFile)
-            .tool(Edit)
-            .tool(WriteFiles)
-            .tool(FileOps)
-            .tool(ListDirectory)
-            .tool(Tree)
-            .build();
-
-        // Git operations specialist
-        let git_agent = client
-            .agent(openai::GPT_4)
-            .preamble("You are a git operations specialist. Help users with git commands and repository management. When tools return formatted output, preserve it exactly.")
-            .tool(Git)
-            .tool(ReadFile)
-            .build();
+        // MCP servers are connected once; reloads keep the connections
+        let mcp = McpServers::connect(&config.mcp).await;
+        if !config.mcp.servers.is_empty() {
+            eprintln!("Loaded {} tools from MCP servers", mcp.tools().len());
+        }
+        for (server, error) in mcp.failures() {
+            eprintln!("MCP server '{}' unavailable, its tools are left out: {}", server, error);
+        }
 
-        // Shell execution specialist
-        let execute_agent = client
-            .agent(openai::GPT_4)
-            .preamble("You are a shell command specialist. Help users execute commands safely. When tools return formatted output, preserve it exactly.")
-            .tool(Execute)
-            .build();
+        let approvals = Arc::new(Approvals::new(config.permission_mode));
+        Self::build(
+            config,
+            config_sources,
+            Arc::new(Mutex::new(session)),
+            Arc::new(available_commands),
+            approvals,
+            &mcp,
+        )
+    }
 
-        // Web fetching specialist
-        let web_agent = client
-            .agent(openai::GPT_4)
-            .preamble("You are a web fetching specialist. Help users retrieve content from URLs. When tools return formatted output, preserve it exactly.")
-            .tool(WebFetch)
+    /// Build the agents, tools and router for a configuration
+    fn build(
+        config: Config,
+        config_sources: ConfigSources,
+        session: Arc<Mutex<Session>>,
+        available_commands: Arc<HashSet<String>>,
+        approvals: Arc<Approvals>,
+        mcp: &McpServers,
+    ) -> Result<Self> {
+        let provider = Provider::from_config(&config)?;
+
+        // Built-in agents plus any declared under [agents.<name>]. Every agent honors
+        // the configured model and token limit unless its section overrides them.
+        let tools = ToolRegistry::new(&config, approvals, mcp)?;
+        let specs = agents::agent_specs(&config, &tools)?;
+        let instructions = Instructions::load(tools.workspace().root());
+
+        // Intent classifier - categories are generated from the registered agents
+        let intent_classifier = provider
+            .agent(&config, agents::CLASSIFIER)
+            .preamble(&agents::classifier_preamble(&specs))
             .build();
 
-        // General assistant for everything else
-        let general_agent = client
-            .agent(openai::GPT_4)
-            .preamble("You are Ada, a helpful AI assistant. Answer questions and provide assistance.")
-            .build();
+        let specialists = specs
+            .iter()
+            .map(|spec| {
+                // The planner's default preamble lists the agents it can hand steps to;
+                // it answers in JSON, so project instructions only go to the specialists
+                let preamble = if spec.name == agents::PLANNER {
+                    if spec.preamble.is_empty() {
+                        planner::planner_preamble(&specs)
+                    } else {
+                        spec.preamble.clone()
+                    }
+                } else {
+                    instructions.apply(&spec.preamble)
+                };
+                Specialist {
+                    agent: agents::build_agent(&provider, &config, &tools, spec, &preamble),
+                    spec: spec.clone(),
+                }
+            })
+            .collect();
+        let agents = AgentRegistry::new(specialists);
+        let router = Router::new(&config.routing, &agents)?;
 
-        Self {
+        Ok(Self {
             config,
+            config_sources,
             intent_classifier,
-            code_agent,
-            file_agent,
-            git_agent,
-            execute_agent,
-            web_agent,
-            general_agent,
+            agents,
+            tools,
+            instructions,
             available_commands,
+            router,
+            session,
+        })
+    }
+
+    /// Re-read the config and instruction files and rebuild the agents, sharing
+    /// this instance's session and approvals. None if nothing changed; otherwise the
+    /// rebuilt instance and a summary of what changed.
+    fn reloaded(&self) -> Result<Option<(Self, String)>> {
+        let (config, config_sources) = Config::load(&self.config_sources.overrides)?;
+        let changes = self.config.changes(&config);
+        let instructions_changed = self.instructions.is_stale();
+        if changes.is_empty() && !instructions_changed {
+            return Ok(None);
+        }
+
+        let permission_mode = config.permission_mode;
+        let approvals = self.tools.approvals_handle();
+        let rebuilt = Self::build(
+            config,
+            config_sources,
+            Arc::clone(&self.session),
+            Arc::clone(&self.available_commands),
+            Arc::clone(&approvals),
+            self.tools.mcp(),
+        )?;
+        approvals.set_mode(permission_mode);
+
+        let mut summary = if changes.is_empty() {
+            "instructions reloaded".to_string()
+        } else {
+            format!("config reloaded: {}", changes.join(", "))
+        };
+        if self.config.mcp != rebuilt.config.mcp {
+            summary.push_str(" (restart Ada to connect to changed MCP servers)");
         }
+        Ok(Some((rebuilt, summary)))
+    }
+
+    /// A separate instance for another conversation: same config and tools, its own
+    /// session and approvals
+    /// A new instance for another session; --agent carries over
+    async fn for_session(&self, mut session: Session) -> Result<Self> {
+        session.run_agent = self.session.lock().await.run_agent.clone();
+        Self::build(
+            self.config.clone(),
+            self.config_sources.clone(),
+            Arc::new(Mutex::new(session)),
+            Arc::clone(&self.available_commands),
+            Arc::new(Approvals::new(self.config.permission_mode)),
+            self.tools.mcp(),
+        )
+    }
+
+    /// Files whose changes trigger a reload: the global and project config and
+    /// every instruction file, including ones that don't exist yet
+    fn watched_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Config::config_file_path().into_iter().collect();
+        // Without a project config, watch for one created in the current directory
+        let project = Config::project_config_path()
+            .or_else(|| Some(std::env::current_dir().ok()?.join(".ada").join("config.toml")));
+        paths.extend(project);
+        paths.extend(self.instructions.paths().map(Path::to_path_buf));
+        paths
     }
 
     fn load_path_commands() -> HashSet<String> {
@@ -166,61 +258,394 @@ Respond with ONLY the category name, nothing else.")
         commands
     }
 
-    async fn process_command(&self, input: &str) -> String {
+    async fn process_command(&self, input: &str, events: &EventSender, cancel: &CancellationToken) -> RunResult {
         // Check for special commands
         if input == "/help" {
-            return self.show_help();
+            return RunResult::completed(self.show_help());
+        }
+
+        if input == "/config" {
+            return RunResult::completed(self.config.describe(&self.config_sources));
+        }
+
+        if input == "/clear" {
+            self.session.lock().await.history.clear();
+            return RunResult::completed("Conversation history cleared.");
+        }
+
+        if input == "/sessions" {
+            return RunResult::completed(Self::list_sessions("Use /sessions <id> to reopen one (an id prefix is enough)."));
+        }
+
+        if let Some(id) = input.strip_prefix("/sessions ") {
+            return RunResult::completed(self.resume_session(id.trim()).await);
+        }
+
+        if input == "/agent" || input.starts_with("/agent ") {
+            return RunResult::completed(self.pin_agent(input["/agent".len()..].trim()).await);
+        }
+
+        // Hold the session for the whole turn so concurrent requests can't interleave
+        let mut session = self.session.lock().await;
+        session.push(SessionEntry::User { content: input.to_string() });
+
+        // Mutating tool calls made during this turn ask for approval through `events`
+        self.tools.approvals().attach(events.clone());
+        let mut result = self.run_turn(&mut session, input, events, cancel).await;
+        self.tools.approvals().detach();
+
+        session.push(SessionEntry::Assistant { content: result.display.clone() });
+        if let Err(e) = session.save() {
+            result.display.push_str(&format!("\n\n(Failed to save session: {})", e));
+        }
+
+        result
+    }
+
+    async fn run_turn(
+        &self,
+        session: &mut Session,
+        input: &str,
+        events: &EventSender,
+        cancel: &CancellationToken,
+    ) -> RunResult {
+        // An explicit @agent prefix beats everything else, including direct commands
+        let mut input = input;
+        let mut decision = None;
+        if let Some((name, request)) = router::parse_override(input) {
+            let Some(specialist) = self.agents.get(name) else {
+                return RunResult::failed(format!("Unknown agent '@{}'. Available: {}", name, self.agents.names()));
+            };
+            if request.is_empty() {
+                return RunResult::failed(format!("Nothing to send to @{}. Usage: @{} <request>", name, name));
+            }
+            input = request;
+            decision = Some(RouteDecision {
+                agent: specialist.spec.name.clone(),
+                source: RouteSource::Override,
+            });
         }
 
         // Check if input is a direct shell command (if enabled)
-        if self.config.enable_direct_commands {
-            if let Some(direct_output) = self.try_direct_command(input).await {
-                return direct_output;
+        if decision.is_none() && self.config.enable_direct_commands {
+            let direct = tokio::select! {
+                result = self.try_direct_command(input) => result,
+                _ = cancel.cancelled() => Some(RunResult {
+                    display: format!("Direct Command: {}\n\n⏹ Interrupted", input),
+                    outcome: RunOutcome::Interrupted,
+                }),
+            };
+            if let Some(direct) = direct {
+                // Keep direct command output in the conversation so agents can refer to it
+                session.history.push(Message::user(input));
+                session.history.push(Message::assistant(direct.display.clone()));
+                return direct;
             }
         }
 
-        // First, classify the intent
-        let intent = match self.intent_classifier.prompt(input).await {
-            Ok(classification) => classification.trim().to_lowercase(),
-            Err(e) => {
-                return format!("Error classifying intent: {}", e);
+        // A pinned agent takes everything that wasn't explicitly overridden
+        if decision.is_none() {
+            if let Some(specialist) = session.pinned().and_then(|name| self.agents.get(name)) {
+                decision = Some(RouteDecision {
+                    agent: specialist.spec.name.clone(),
+                    source: RouteSource::Pinned,
+                });
             }
+        }
+
+        // Obvious requests are routed by keyword rules; only ambiguous ones pay for
+        // a classifier round trip
+        let decision = match decision.or_else(|| self.router.route(input)) {
+            Some(decision) => decision,
+            None => match self.classify(&session.history, input, events, cancel).await {
+                Ok(decision) => decision,
+                Err(message) => return RunResult::failed(message),
+            },
+        };
+        let via = decision.source.to_string();
+
+        // Decisions only ever name registered agents; general covers anything stale
+        let specialist = self.agents.get(&decision.agent).unwrap_or_else(|| self.agents.general());
+        let intent = specialist.spec.name.clone();
+        let agent_name = specialist.spec.display_name.as_str();
+        session.push(SessionEntry::Route {
+            intent: intent.clone(),
+            agent: agent_name.to_string(),
+            via: via.clone(),
+        });
+        let _ = events.send(AgentEvent::Routed {
+            intent: intent.clone(),
+            agent: agent_name.to_string(),
+            via: via.clone(),
+        });
+
+        // Route to appropriate specialist agent (or the planner for compound requests).
+        // Every agent reads from and appends to the same history, so routing can
+        // switch between specialists without losing the conversation. Output is
+        // streamed through `events` as it arrives.
+        let history_start = session.history.len();
+        let run = if intent == agents::PLANNER {
+            self.run_plan(session, &specialist.agent, input, events, cancel).await
+        } else {
+            let depth = self.depth_for(specialist);
+            let run = specialist.agent.stream_with_history(input, &mut session.history, depth, events, cancel).await;
+            Self::format_run(run)
         };
+        session.record_tool_calls(history_start);
 
-        // Map intent to agent name for display
-        let agent_name = match intent.as_str() {
-            "code_search" => "Code Search",
-            "file_ops" => "File Operations",
-            "git" => "Git Operations",
-            "execution" => "Shell Execution",
-            "web" => "Web Fetching",
-            _ => "General Assistant",
+        let display = if self.config.show_intent {
+            format!("Intent: {} ({}) → [{}]\n\n{}", intent, via, agent_name, run.display.trim_start())
+        } else {
+            format!("[{}]\n\n{}", agent_name, run.display.trim_start())
         };
+        RunResult { display, ..run }
+    }
 
-        // Route to appropriate specialist agent using configured multi-turn depth
+    /// Multi-turn depth for an agent; agents without tools need fewer turns
+    fn depth_for(&self, specialist: &Specialist) -> usize {
         let depth = self.config.multi_turn_depth;
-        let result = match intent.as_str() {
-            "code_search" => self.code_agent.prompt(input).multi_turn(depth).await,
-            "file_ops" => self.file_agent.prompt(input).multi_turn(depth).await,
-            "git" => self.git_agent.prompt(input).multi_turn(depth).await,
-            "execution" => self.execute_agent.prompt(input).multi_turn(depth).await,
-            "web" => self.web_agent.prompt(input).multi_turn(depth).await,
-            _ => self.general_agent.prompt(input).multi_turn(depth / 2).await,
+        if specialist.spec.tools.is_empty() { depth / 2 } else { depth }
+    }
+
+    /// Note how a run ended below its output; partial output is kept when it failed
+    /// or was interrupted
+    fn format_run(mut run: RunResult) -> RunResult {
+        match &run.outcome {
+            RunOutcome::Completed => {}
+            RunOutcome::Interrupted => run.display.push_str("\n\n⏹ Interrupted"),
+            RunOutcome::Failed(e) => run.display.push_str(&format!("\n\nError calling AI agent: {}", e)),
+        }
+        run
+    }
+
+    /// Split a compound request into steps with the planner and run each step on
+    /// its specialist. Steps share the conversation history, so each one sees the
+    /// results of the steps before it.
+    async fn run_plan(
+        &self,
+        session: &mut Session,
+        planner: &AnyAgent,
+        input: &str,
+        events: &EventSender,
+        cancel: &CancellationToken,
+    ) -> RunResult {
+        let reply = tokio::select! {
+            result = planner.prompt(Self::with_previous_request(&session.history, input), events) => result,
+            _ = cancel.cancelled() => return RunResult {
+                display: "⏹ Interrupted".to_string(),
+                outcome: RunOutcome::Interrupted,
+            },
+        };
+        let steps = match reply
+            .map_err(|e| format!("Error calling planner: {}", e))
+            .and_then(|reply| planner::parse_plan(&reply))
+        {
+            Ok(steps) => steps,
+            Err(message) => return RunResult::failed(message),
         };
 
-        match result {
-            Ok(response) => {
-                if self.config.show_intent {
-                    format!("Intent: {} → [{}]\n\n{}", intent, agent_name, response)
-                } else {
-                    format!("[{}]\n\n{}", agent_name, response)
+        // Plan and step status lines go to the live view and the final output alike
+        let mut display = String::new();
+        let emit = |display: &mut String, text: String| {
+            display.push_str(&text);
+            let _ = events.send(AgentEvent::Text(text));
+        };
+        // Unknown agents (or a nested plan) fall back to the general agent
+        let resolved: Vec<(&Specialist, &str)> = steps
+            .iter()
+            .map(|step| {
+                let specialist = self
+                    .agents
+                    .get(&step.agent)
+                    .filter(|s| s.spec.name != agents::PLANNER)
+                    .unwrap_or_else(|| self.agents.general());
+                (specialist, step.task.as_str())
+            })
+            .collect();
+        let listing: Vec<(&str, &str)> = resolved
+            .iter()
+            .map(|(specialist, task)| (specialist.spec.display_name.as_str(), *task))
+            .collect();
+        emit(&mut display, planner::format_plan(&listing));
+
+        let total = resolved.len();
+        let mut outcome = RunOutcome::Completed;
+        for (i, (specialist, task)) in resolved.iter().enumerate() {
+            let number = i + 1;
+            let agent_name = specialist.spec.display_name.as_str();
+            emit(&mut display, format!("\n▶ Step {}/{} · {}\n", number, total, agent_name));
+            session.push(SessionEntry::Route {
+                intent: specialist.spec.name.clone(),
+                agent: agent_name.to_string(),
+                via: format!("plan step {}/{}", number, total),
+            });
+
+            let prompt = format!(
+                "You are carrying out step {} of {} of this request: \"{}\"\nResults of earlier steps are in the conversation above.\n\nYour step: {}",
+                number, total, input, task
+            );
+            let depth = self.depth_for(specialist);
+            let run = specialist.agent.stream_with_history(&prompt, &mut session.history, depth, events, cancel).await;
+            display.push_str(&run.display);
+
+            match &run.outcome {
+                RunOutcome::Completed => emit(&mut display, format!("\n✓ Step {} complete\n", number)),
+                RunOutcome::Interrupted => {
+                    emit(&mut display, format!("\n⏹ Interrupted during step {}; remaining steps skipped\n", number));
+                }
+                RunOutcome::Failed(e) => {
+                    emit(&mut display, format!("\n✗ Step {} failed: {}; remaining steps skipped\n", number, e));
                 }
             }
-            Err(e) => format!("Error calling AI agent: {}", e),
+            if run.outcome != RunOutcome::Completed {
+                outcome = run.outcome;
+                break;
+            }
+        }
+
+        RunResult {
+            display: display.trim_end().to_string(),
+            outcome,
         }
     }
 
-    async fn try_direct_command(&self, input: &str) -> Option<String> {
+    /// Saved sessions, most recent first, followed by `hint` on how to open one
+    fn list_sessions(hint: &str) -> String {
+        let sessions = match Session::list() {
+            Ok(sessions) => sessions,
+            Err(e) => return format!("Failed to list sessions: {}", e),
+        };
+
+        if sessions.is_empty() {
+            return "No saved sessions yet.".to_string();
+        }
+
+        let mut output = String::from("Saved sessions (most recent first):\n\n");
+        for summary in sessions.iter().take(20) {
+            output.push_str(&format!(
+                "  {}  {:>8}  {:>3} turns  {}\n      {}\n",
+                summary.id,
+                session::format_age(summary.updated_at),
+                summary.turns,
+                summary.title,
+                summary.cwd
+            ));
+        }
+        output.push('\n');
+        output.push_str(hint);
+        output
+    }
+
+    async fn resume_session(&self, id: &str) -> String {
+        let resumed = match Session::load(id) {
+            Ok(session) => session,
+            Err(e) => return format!("{}", e),
+        };
+
+        let mut session = self.session.lock().await;
+        if let Err(e) = session.save() {
+            return format!("Failed to save current session: {}", e);
+        }
+
+        let message = format!("Resumed session {} ({} turns)", resumed.id, resumed.turns());
+        let run_agent = session.run_agent.take();
+        *session = resumed;
+        session.run_agent = run_agent;
+        self.tools.approvals().reset();
+        message
+    }
+
+    async fn session_snapshot(&self) -> (String, Vec<SessionEntry>) {
+        let session = self.session.lock().await;
+        (session.id.clone(), session.entries.clone())
+    }
+
+    /// Ask the LLM classifier for the intent (with the previous request as context
+    /// for follow-ups). The error is the message to show the user.
+    async fn classify(
+        &self,
+        history: &[Message],
+        input: &str,
+        events: &EventSender,
+        cancel: &CancellationToken,
+    ) -> std::result::Result<RouteDecision, String> {
+        let classification = tokio::select! {
+            result = self.intent_classifier.prompt(Self::with_previous_request(history, input), events) => result,
+            _ = cancel.cancelled() => return Err("⏹ Interrupted".to_string()),
+        };
+
+        let reply = classification.map_err(|e| format!("Error classifying intent: {}", e))?;
+        match self.agents.parse_classification(&reply) {
+            Some(specialist) => Ok(RouteDecision {
+                agent: specialist.spec.name.clone(),
+                source: RouteSource::Classifier,
+            }),
+            None => Err(format!(
+                "The intent classifier returned an unknown label: '{}'. Prefix your request with one of {} to pick an agent.",
+                reply.trim(),
+                self.agents.names()
+            )),
+        }
+    }
+
+    /// /agent shows the pinned agent, /agent <name> pins one, /agent off unpins
+    async fn pin_agent(&self, name: &str) -> String {
+        let mut session = self.session.lock().await;
+        let name = name.trim_start_matches('@');
+
+        match name {
+            "" => match session.pinned() {
+                Some(agent) => format!("Routing is pinned to @{}. Use /agent off to go back to automatic routing.", agent),
+                None => format!("Routing is automatic. Use /agent <name> to pin one of {}.", self.agents.names()),
+            },
+            "off" | "auto" | "clear" => {
+                session.pinned_agent = None;
+                session.run_agent = None;
+                "Routing is automatic again.".to_string()
+            }
+            _ => match self.agents.get(name) {
+                Some(specialist) => {
+                    session.pinned_agent = Some(specialist.spec.name.clone());
+                    session.run_agent = None;
+                    format!("All requests now go to @{} until you run /agent off.", specialist.spec.name)
+                }
+                None => format!("Unknown agent '{}'. Available: {}", name, self.agents.names()),
+            },
+        }
+    }
+
+    /// Send every request of this run to `name` (--agent). Unlike /agent, the choice
+    /// isn't saved with the session.
+    async fn pin_agent_for_run(&self, name: &str) -> Result<()> {
+        let Some(specialist) = self.agents.get(name.trim_start_matches('@')) else {
+            anyhow::bail!("Unknown agent '{}'. Available: {}", name, self.agents.names());
+        };
+        self.session.lock().await.run_agent = Some(specialist.spec.name.clone());
+        Ok(())
+    }
+
+    /// Prefix the previous request so one-shot agents can resolve follow-ups
+    fn with_previous_request(history: &[Message], input: &str) -> String {
+        match Self::last_user_text(history) {
+            Some(previous) => format!("Previous request: {}\n\nCurrent request: {}", previous, input),
+            None => input.to_string(),
+        }
+    }
+
+    /// Most recent plain-text user turn in the conversation (tool results are skipped)
+    fn last_user_text(history: &[Message]) -> Option<String> {
+        use rig::completion::message::UserContent;
+
+        history.iter().rev().find_map(|message| match message {
+            Message::User { content } => content.iter().find_map(|item| match item {
+                UserContent::Text(text) => Some(text.text.clone()),
+                _ => None,
+            }),
+            _ => None,
+        })
+    }
+
+    async fn try_direct_command(&self, input: &str) -> Option<RunResult> {
         let input = input.trim();
         let mut tokens = input.split_whitespace();
 
@@ -255,18 +680,27 @@ Respond with ONLY the category name, nothing else.")
             }
         }
 
+        // Direct commands are execute calls, so [tools] restrictions apply to them too
+        if !self.tools.is_enabled("execute") {
+            return None;
+        }
+
         // Execute the command directly
-        use rig::tool::Tool;
-        let result = Execute
-            .call(tools::execute::ExecuteArgs {
-                command: input.to_string(),
-                working_dir: None,
-            })
+        let result = self
+            .tools
+            .call("execute", serde_json::json!({ "command": input }))
             .await;
 
         match result {
-            Ok(output) => Some(format!("Direct Command: {}\n\n{}", first_word, output)),
-            Err(e) => Some(format!("Command failed: {}", e)),
+            Ok(output) => {
+                let output = output.as_str().unwrap_or_default();
+                // The command ran but exited with an error, which -p reports like a failed tool call
+                if tools::Execute::failed(output) {
+                    self.tools.record_failure();
+                }
+                Some(RunResult::completed(format!("Direct Command: {}\n\n{}", first_word, output)))
+            }
+            Err(e) => Some(RunResult::failed(format!("Command failed: {}", e))),
         }
     }
 
@@ -277,11 +711,35 @@ Respond with ONLY the category name, nothing else.")
         if let Ok(config_path) = Config::config_file_path() {
             help.push_str(&format!("Config: {}\n", config_path.display()));
         }
-        help.push_str(&format!("Model: {} | Multi-turn depth: {} | Direct commands: {}\n\n",
+        help.push_str(&format!("Provider: {}{}\n",
+            self.config.provider.kind,
+            self.config.provider.base_url.as_deref().map(|url| format!(" ({})", url)).unwrap_or_default()
+        ));
+        help.push_str(&format!("Model: {} | Max tokens: {} | Multi-turn depth: {} | Direct commands: {}\n",
             self.config.model,
+            self.config.max_tokens,
             self.config.multi_turn_depth,
             if self.config.enable_direct_commands { "enabled" } else { "disabled" }
         ));
+        help.push_str(&format!("Permission mode: {} | Workspace: {}\n",
+            self.tools.approvals().mode(),
+            self.tools.workspace().root().display()
+        ));
+        for path in self.instructions.sources() {
+            help.push_str(&format!("Instructions: {}\n", path.display()));
+        }
+
+        // Show per-agent overrides, sorted for stable output
+        let mut overrides: Vec<_> = self.config.agents.keys().collect();
+        overrides.sort();
+        for name in overrides {
+            help.push_str(&format!("  [agents.{}] model: {} | max tokens: {}\n",
+                name,
+                self.config.model_for(name),
+                self.config.max_tokens_for(name)
+            ));
+        }
+        help.push('\n');
 
         if self.config.enable_direct_commands {
             help.push_str(&format!("Direct Commands: {} commands available from PATH\n", self.available_commands.len()));
@@ -290,34 +748,27 @@ Respond with ONLY the category name, nothing else.")
 
         help.push_str("I automatically route other requests to specialized agents:\n\n");
 
-        help.push_str("Code Search Agent:\n");
-        help.push_str("  - grep - Search file contents with regex\n");
-        help.push_str("  - glob - Find files by pattern (*.rs, **/*.toml)\n");
-        help.push_str("  - search_directory - Search directories\n");
-        help.push_str("  - read_file - Read files\n\n");
-
-        help.push_str("File Operations Agent:\n");
-        help.push_str("  - read_file - Read file contents with line numbers\n");
-        help.push_str("  - edit - Replace text in files (shows diffs)\n");
-        help.push_str("  - write_files - Write multiple files at once\n");
-        help.push_str("  - file_ops - Delete, move, copy files\n");
-        help.push_str("  - list_directory - List files and folders\n");
-        help.push_str("  - tree - Visual directory structure\n\n");
-
-        help.push_str("Git Operations Agent:\n");
-        help.push_str("  - git - Git operations (status, diff, log, commit)\n\n");
-
-        help.push_str("Shell Execution Agent:\n");
-        help.push_str("  - execute - Run shell commands\n\n");
-
-        help.push_str("Web Fetching Agent:\n");
-        help.push_str("  - webfetch - Fetch content from URLs\n\n");
+        for specialist in self.agents.iter() {
+            let spec = &specialist.spec;
+            help.push_str(&format!("{} (@{}):\n", spec.display_name, spec.name));
+            help.push_str(&format!("  {}\n", spec.description));
+            if !spec.tools.is_empty() {
+                help.push_str(&format!("  Tools: {}\n", spec.tools.join(", ")));
+            }
+            help.push('\n');
+        }
 
-        help.push_str("General Agent:\n");
-        help.push_str("  - Answers general questions and provides assistance\n\n");
+        help.push_str(&self.tools.describe());
+        help.push('\n');
 
         help.push_str("Commands:\n");
-        help.push_str("  /help - Show this help message\n\n");
+        help.push_str("  /help - Show this help message\n");
+        help.push_str("  /config - Show the effective configuration and where each value came from\n");
+        help.push_str("  /clear - Forget the conversation so far\n");
+        help.push_str("  /sessions - List saved sessions\n");
+        help.push_str("  /sessions <id> - Reopen a saved session\n");
+        help.push_str("  /agent <name> - Send every request to one agent (/agent off to undo)\n");
+        help.push_str("  @<agent> <request> - Send one request to a specific agent\n\n");
 
         help.push_str("Examples:\n");
         help.push_str("  - \"find all TODO comments in src\"\n");
@@ -330,53 +781,181 @@ Respond with ONLY the category name, nothing else.")
     }
 }
 
+/// A request running on its own task
+struct ActiveRequest {
+    task: tokio::task::JoinHandle<RunResult>,
+    events: events::EventReceiver,
+    cancel: CancellationToken,
+}
+
+impl ActiveRequest {
+    fn start(ada: Arc<Ada>, input: String) -> Self {
+        let (events_tx, events) = events::channel();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let task = tokio::spawn(async move { ada.process_command(&input, &events_tx, &token).await });
+
+        Self { task, events, cancel }
+    }
+}
+
+/// How long the watched files must be quiet before a reload
+const RELOAD_DELAY: Duration = Duration::from_millis(250);
+
+/// Watch the instance's config and instruction files; a failure is reported in the
+/// transcript and leaves reloading off
+fn watch(ada: &Ada, app: &mut App) -> Option<ConfigWatcher> {
+    match ConfigWatcher::new(ada.watched_paths()) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            app.add_message(MessageRole::System, format!("Not watching config files: {:#}", e));
+            None
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Check for --version flag
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 && (args[1] == "--version" || args[1] == "-v") {
-        println!("ada {}", env!("CARGO_PKG_VERSION"));
-        return Ok(());
+    let cli = Cli::parse();
+    cli.global.apply()?;
+    let overrides = cli.global.overrides();
+
+    if let Some(command) = cli.command {
+        let succeeded = cli::run(command, &cli.global).await?;
+        std::process::exit(if succeeded { 0 } else { 1 });
+    }
+
+    // --continue picks up the most recent session, --resume <id> a specific one
+    let session = match (&cli.resume, cli.continue_session) {
+        (Some(id), _) if id.is_empty() => {
+            println!("{}", Ada::list_sessions("Run ada --resume <id> to reopen one."));
+            return Ok(());
+        }
+        (Some(id), _) => Session::load(id)?,
+        (None, true) => Session::latest()?,
+        (None, false) => Session::new(),
+    };
+
+    let ada = Ada::new(session, &overrides).await?;
+    if let Some(agent) = &cli.global.agent {
+        ada.pin_agent_for_run(agent).await?;
+    }
+    let mut ada = Arc::new(ada);
+
+    // ada -p "prompt" answers once on stdout and exits with a status scripts can check
+    if let Some(prompt) = cli.prompt {
+        let prompt = headless::prompt_with_stdin(prompt)?;
+        std::process::exit(headless::run(ada, prompt, cli.output).await);
     }
 
     // Don't initialize tracing to avoid interfering with TUI
     // tracing_subscriber::fmt::init();
 
-    let ada = Arc::new(Ada::new());
     let mut app = App::new();
+    app.show_intent = ada.config.show_intent;
+    let (mut session_id, entries) = ada.session_snapshot().await;
+    if !entries.is_empty() {
+        app.load_transcript(&entries);
+        app.add_message(MessageRole::System, format!("Resumed session {}", session_id));
+    }
     let mut ui = UI::new()?;
 
-    // Main event loop
-    loop {
-        let message_count_before = app.messages.len();
-        ui.handle_events(&mut app)?;
+    // Config and instruction edits apply without a restart
+    let mut watcher = watch(&ada, &mut app);
+    let mut reload_at: Option<Instant> = None;
 
-        if app.should_quit {
-            break;
+    // Main event loop. Agent work runs on its own task and reports back over a
+    // channel, so the UI keeps drawing and taking input while a request runs.
+    let mut active: Option<ActiveRequest> = None;
+    loop {
+        // Editors often write a file in several steps; wait for them to settle
+        if let Some(watcher) = watcher.as_mut() {
+            if !watcher.changed().is_empty() {
+                reload_at = Some(Instant::now() + RELOAD_DELAY);
+            }
         }
 
-        // Check if a new message was added
-        if app.messages.len() > message_count_before {
-            let last_message = app.messages.last().unwrap();
-            if matches!(last_message.role, MessageRole::User) {
-                let input = last_message.content.clone();
-                let ada = Arc::clone(&ada);
+        // A running request keeps the instance it started with; later ones get the
+        // rebuilt agents. A broken file leaves the current config in effect.
+        if reload_at.is_some_and(|at| Instant::now() >= at) {
+            reload_at = None;
+            match ada.reloaded() {
+                Ok(Some((rebuilt, summary))) => {
+                    ada = Arc::new(rebuilt);
+                    app.show_intent = ada.config.show_intent;
+                    app.add_message(MessageRole::System, summary);
+                    watcher = watch(&ada, &mut app);
+                }
+                Ok(None) => {}
+                Err(e) => app.add_message(
+                    MessageRole::System,
+                    format!("config not reloaded, keeping the previous settings: {:#}", e),
+                ),
+            }
+        }
 
-                // Set processing state and redraw
+        // Send the next queued message once the previous request has finished
+        if active.is_none() {
+            if let Some(input) = app.queued.pop_front() {
+                app.add_message(MessageRole::User, input.clone());
                 app.is_processing = true;
-                ui.draw(&app)?;
+                active = Some(ActiveRequest::start(Arc::clone(&ada), input));
+            }
+        }
 
-                // Process the command
-                let response = ada.process_command(&input).await;
+        let mut response = None;
+        match active.as_mut() {
+            Some(request) => {
+                tokio::select! {
+                    Some(event) = request.events.recv() => app.apply_event(event),
+                    result = &mut request.task => {
+                        response = Some(match result {
+                            Ok(result) => result.display,
+                            Err(e) => format!("Error: agent task failed: {}", e),
+                        });
+                    }
+                    _ = tokio::time::sleep(Duration::from_millis(16)) => {}
+                }
+                ui.handle_events(&mut app, Duration::ZERO)?;
+            }
+            None => ui.handle_events(&mut app, Duration::from_millis(100))?,
+        }
+
+        if app.should_quit {
+            break;
+        }
 
-                // Clear processing state
-                app.is_processing = false;
+        // Answer an approval the user made in the modal
+        if let Some((id, decision)) = app.approval_response.take() {
+            ada.tools.approvals().resolve(id, decision);
+        }
+
+        // Esc cancels the token, which aborts the request and any running tool
+        if let Some(request) = &active {
+            if app.interrupt_requested && !request.cancel.is_cancelled() {
+                request.cancel.cancel();
+            }
+        }
 
-                app.add_message(MessageRole::Assistant, response);
+        if let Some(response) = response {
+            active = None;
+
+            // Clear processing state
+            app.is_processing = false;
+            app.interrupt_requested = false;
+            app.approval = None;
+
+            // A /sessions <id> command swaps the session; show its transcript instead
+            let (current_id, entries) = ada.session_snapshot().await;
+            if current_id != session_id {
+                session_id = current_id;
+                app.load_transcript(&entries);
+                app.add_message(MessageRole::System, response);
+            } else {
+                app.finish_response(response);
             }
         }
 
-        // Only redraw when needed
         ui.draw(&app)?;
     }
 
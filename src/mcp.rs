@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use rig::tool::Tool;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::approval::{Approvals, PermissionMode};
+use crate::config::Config;
+use crate::tools::{McpServers, ToolRegistry};
+
+/// Protocol revisions this server speaks, newest first
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve the tool registry as a Model Context Protocol server on stdin/stdout until
+/// the client closes stdin. Calls go through the same policy and workspace checks as
+/// agent tool calls; MCP clients confirm calls with their user, so Ada doesn't ask
+/// again, but paths outside the workspace are still refused.
+pub async fn serve(overrides: &[String]) -> Result<()> {
+    let (config, _) = Config::load(overrides)?;
+    // Only Ada's own tools; tools of the servers Ada itself uses aren't passed on
+    let tools = ToolRegistry::new(&config, Arc::new(Approvals::new(PermissionMode::Yolo)), &McpServers::offline(&config.mcp))?;
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await.context("Failed to read from stdin")? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(&tools, message).await,
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, format!("Invalid JSON: {}", e))),
+        };
+        // Notifications get no response
+        if let Some(response) = response {
+            stdout.write_all(format!("{}\n", response).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Response to one JSON-RPC message, or None for a notification
+async fn handle(tools: &ToolRegistry, message: Value) -> Option<Value> {
+    // Notifications have no id; responses to us have no method
+    let id = message.get("id").cloned()?;
+    let method = message.get("method")?.as_str().unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => {
+            // Answer with the client's revision when we speak it, otherwise our newest
+            let requested = params["protocolVersion"].as_str().unwrap_or_default();
+            let version = PROTOCOL_VERSIONS
+                .iter()
+                .find(|v| **v == requested)
+                .unwrap_or(&PROTOCOL_VERSIONS[0]);
+            json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "ada", "version": env!("CARGO_PKG_VERSION") },
+            })
+        }
+        "ping" => json!({}),
+        "tools/list" => list_tools(tools),
+        "tools/call" => match call_tool(tools, &params).await {
+            Ok(result) => result,
+            Err(message) => return Some(error(id, INVALID_PARAMS, message)),
+        },
+        method => return Some(error(id, METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+/// Every enabled tool with its input schema
+fn list_tools(tools: &ToolRegistry) -> Value {
+    let listed: Vec<Value> = tools
+        .iter()
+        .filter(|spec| tools.is_enabled(&spec.name))
+        .map(|spec| {
+            json!({
+                "name": spec.name,
+                "description": spec.description,
+                "inputSchema": spec.parameters,
+                "annotations": { "readOnlyHint": !spec.mutates },
+            })
+        })
+        .collect();
+    json!({ "tools": listed })
+}
+
+/// Run a tool. Tool failures are results with isError so the client's model sees
+/// them; only a malformed request is a protocol error.
+async fn call_tool(tools: &ToolRegistry, params: &Value) -> Result<Value, String> {
+    let name = params["name"].as_str().ok_or("Missing tool name")?;
+    let tool = tools.get(name).ok_or_else(|| format!("Unknown or disabled tool '{}'", name))?;
+    let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+    let (text, is_error) = match tool.call(arguments).await {
+        Ok(Value::String(text)) => (text, false),
+        Ok(output) => (serde_json::to_string_pretty(&output).unwrap_or_default(), false),
+        Err(e) => (e.to_string(), true),
+    };
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    }))
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
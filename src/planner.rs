@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+use crate::agents::{AgentSpec, GENERAL, PLANNER};
+
+/// Planner preamble listing the agents steps can be assigned to
+pub fn planner_preamble(specs: &[AgentSpec]) -> String {
+    let mut preamble = String::from(
+        "You are a planner. Split the user's request into the shortest ordered list of steps, where each step is handled by exactly one specialist agent:\n",
+    );
+    for spec in specs.iter().filter(|s| s.name != PLANNER) {
+        preamble.push_str(&format!("- {}: {}\n", spec.name, spec.description));
+    }
+    preamble.push_str(&format!(
+        "\nLater steps can see the results of earlier ones, so refer to them instead of guessing (e.g. \"add the field to the file found in the previous step\"). Use {} for steps that only need to answer or summarize.
+
+Respond with ONLY a JSON array, nothing else, in this form:
+[{{\"agent\": \"code\", \"task\": \"Find where the config file is parsed\"}}, {{\"agent\": \"git\", \"task\": \"Commit the change\"}}]",
+        GENERAL
+    ));
+    preamble
+}
+
+/// One step of a plan: which agent runs it and what it should do
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlanStep {
+    pub agent: String,
+    pub task: String,
+}
+
+/// Parse the planner's reply, tolerating code fences or prose around the JSON array
+pub fn parse_plan(reply: &str) -> Result<Vec<PlanStep>, String> {
+    let (Some(start), Some(end)) = (reply.find('['), reply.rfind(']')) else {
+        return Err(format!("The planner did not return a plan: {}", reply.trim()));
+    };
+    if end < start {
+        return Err(format!("The planner did not return a plan: {}", reply.trim()));
+    }
+
+    let steps: Vec<PlanStep> = serde_json::from_str(&reply[start..=end])
+        .map_err(|e| format!("The planner returned an invalid plan ({}): {}", e, reply.trim()))?;
+
+    if steps.is_empty() {
+        return Err("The planner returned an empty plan".to_string());
+    }
+
+    Ok(steps)
+}
+
+/// Numbered plan listing shown before the steps run, as (agent display name, task)
+pub fn format_plan(steps: &[(&str, &str)]) -> String {
+    let mut output = String::from("Plan:\n");
+    for (i, (agent, task)) in steps.iter().enumerate() {
+        output.push_str(&format!("  {}. [{}] {}\n", i + 1, agent, task));
+    }
+    output
+}
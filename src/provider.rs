@@ -0,0 +1,448 @@
+use anyhow::{Context, Result, bail};
+use futures::{Stream, StreamExt};
+use rig::OneOrMany;
+use rig::agent::{Agent, AgentBuilder, MultiTurnStreamItem};
+use rig::completion::message::{AssistantContent, ToolCall, ToolResult, ToolResultContent, UserContent};
+use rig::completion::{Message, Prompt, PromptError};
+use rig::prelude::*;
+use rig::providers::openai::responses_api::ResponsesCompletionModel;
+use rig::providers::{anthropic, openai};
+use rig::streaming::{StreamedAssistantContent, StreamedUserContent, StreamingPrompt};
+use rig::tool::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::Config;
+use crate::events::{self, AgentEvent, EventSender};
+
+/// Which LLM backend the agents talk to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProviderKind {
+    /// OpenAI (Responses API)
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    /// Anthropic Messages API
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    /// Any server speaking the OpenAI Chat Completions API (vLLM, llama.cpp, Ollama, ...)
+    #[serde(rename = "openai_compatible")]
+    OpenAICompatible,
+    /// OpenAI-compatible server on this machine that needs no API key
+    #[serde(rename = "local")]
+    Local,
+}
+
+impl std::fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ProviderKind::OpenAI => "openai",
+            ProviderKind::Anthropic => "anthropic",
+            ProviderKind::OpenAICompatible => "openai_compatible",
+            ProviderKind::Local => "local",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ProviderKind {
+    /// Whether `model` looks like one of this provider's models. Self-hosted
+    /// backends serve arbitrary names, so everything passes there.
+    pub fn recognizes_model(&self, model: &str) -> bool {
+        let prefixes: &[&str] = match self {
+            ProviderKind::OpenAI => &["gpt-", "chatgpt-", "o1", "o3", "o4", "codex-", "ft:"],
+            ProviderKind::Anthropic => &["claude-"],
+            ProviderKind::OpenAICompatible | ProviderKind::Local => return true,
+        };
+        prefixes.iter().any(|prefix| model.starts_with(prefix))
+    }
+
+    /// Example model names, for error messages
+    pub fn model_hint(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenAI => "e.g. gpt-4o, gpt-4o-mini, o3-mini",
+            ProviderKind::Anthropic => "e.g. claude-sonnet-4-5, claude-3-5-haiku-latest",
+            ProviderKind::OpenAICompatible | ProviderKind::Local => "any name the server accepts",
+        }
+    }
+}
+
+/// Default endpoint for local mode (llama.cpp server)
+pub const DEFAULT_LOCAL_BASE_URL: &str = "http://localhost:8080/v1";
+
+/// A configured client for one of the supported backends
+pub enum Provider {
+    OpenAI(openai::Client),
+    /// OpenAI-compatible endpoint; always uses the Chat Completions API
+    Compatible(openai::Client),
+    Anthropic(anthropic::Client),
+}
+
+impl Provider {
+    /// Build the client described by the [provider] section of the config
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let settings = &config.provider;
+        let api_key = |default_env: &str| -> Result<String> {
+            let env_name = settings.api_key_env.as_deref().unwrap_or(default_env);
+            std::env::var(env_name)
+                .with_context(|| format!("{} environment variable not set (required by the {} provider)", env_name, settings.kind))
+        };
+
+        let provider = match settings.kind {
+            ProviderKind::OpenAI => {
+                let key = api_key("OPENAI_API_KEY")?;
+                let mut builder = openai::Client::builder(&key);
+                if let Some(base_url) = &settings.base_url {
+                    builder = builder.base_url(base_url);
+                }
+                Provider::OpenAI(builder.build().context("Failed to create OpenAI client")?)
+            }
+            ProviderKind::Anthropic => {
+                let key = api_key("ANTHROPIC_API_KEY")?;
+                let mut builder = anthropic::Client::builder(&key);
+                if let Some(base_url) = &settings.base_url {
+                    builder = builder.base_url(base_url);
+                }
+                Provider::Anthropic(builder.build().context("Failed to create Anthropic client")?)
+            }
+            ProviderKind::OpenAICompatible => {
+                let Some(base_url) = &settings.base_url else {
+                    bail!("provider.base_url is required for the openai_compatible provider");
+                };
+                // Many self-hosted servers don't check the key, so it is optional here
+                let key = match &settings.api_key_env {
+                    Some(_) => api_key("OPENAI_API_KEY")?,
+                    None => "none".to_string(),
+                };
+                let client = openai::Client::builder(&key)
+                    .base_url(base_url)
+                    .build()
+                    .context("Failed to create OpenAI-compatible client")?;
+                Provider::Compatible(client)
+            }
+            ProviderKind::Local => {
+                let base_url = settings.base_url.as_deref().unwrap_or(DEFAULT_LOCAL_BASE_URL);
+                let client = openai::Client::builder("local")
+                    .base_url(base_url)
+                    .build()
+                    .context("Failed to create local client")?;
+                Provider::Compatible(client)
+            }
+        };
+
+        Ok(provider)
+    }
+
+    /// Start building an agent with the model and token limit configured for `name`
+    pub fn agent(&self, config: &Config, name: &str) -> AnyAgentBuilder {
+        let model = config.model_for(name);
+        let max_tokens = config.max_tokens_for(name) as u64;
+
+        match self {
+            Provider::OpenAI(client) => {
+                AnyAgentBuilder::Responses(client.agent(model).max_tokens(max_tokens))
+            }
+            Provider::Compatible(client) => {
+                let model = client.completion_model(model).completions_api();
+                AnyAgentBuilder::Completions(AgentBuilder::new(model).max_tokens(max_tokens))
+            }
+            Provider::Anthropic(client) => {
+                AnyAgentBuilder::Anthropic(client.agent(model).max_tokens(max_tokens))
+            }
+        }
+    }
+}
+
+/// Agent builder for whichever backend is configured
+pub enum AnyAgentBuilder {
+    Responses(AgentBuilder<ResponsesCompletionModel>),
+    Completions(AgentBuilder<openai::completion::CompletionModel>),
+    Anthropic(AgentBuilder<anthropic::completion::CompletionModel>),
+}
+
+impl AnyAgentBuilder {
+    pub fn preamble(self, preamble: &str) -> Self {
+        match self {
+            Self::Responses(b) => Self::Responses(b.preamble(preamble)),
+            Self::Completions(b) => Self::Completions(b.preamble(preamble)),
+            Self::Anthropic(b) => Self::Anthropic(b.preamble(preamble)),
+        }
+    }
+
+    pub fn tool(self, tool: impl Tool + 'static) -> Self {
+        match self {
+            Self::Responses(b) => Self::Responses(b.tool(tool)),
+            Self::Completions(b) => Self::Completions(b.tool(tool)),
+            Self::Anthropic(b) => Self::Anthropic(b.tool(tool)),
+        }
+    }
+
+    pub fn build(self) -> AnyAgent {
+        match self {
+            Self::Responses(b) => AnyAgent::Responses(b.build()),
+            Self::Completions(b) => AnyAgent::Completions(b.build()),
+            Self::Anthropic(b) => AnyAgent::Anthropic(b.build()),
+        }
+    }
+}
+
+/// Agent for whichever backend is configured
+pub enum AnyAgent {
+    Responses(Agent<ResponsesCompletionModel>),
+    Completions(Agent<openai::completion::CompletionModel>),
+    Anthropic(Agent<anthropic::completion::CompletionModel>),
+}
+
+/// Run the same expression against the concrete agent inside an `AnyAgent`
+macro_rules! with_agent {
+    ($any:expr, $agent:ident => $body:expr) => {
+        match $any {
+            AnyAgent::Responses($agent) => $body,
+            AnyAgent::Completions($agent) => $body,
+            AnyAgent::Anthropic($agent) => $body,
+        }
+    };
+}
+
+impl AnyAgent {
+    /// Single prompt without history or tool turns; its token usage goes to `events`
+    pub async fn prompt(&self, input: impl Into<Message>, events: &EventSender) -> Result<String, PromptError> {
+        let input = input.into();
+        let response = with_agent!(self, agent => agent.prompt(input).extended_details().await)?;
+        let _ = events.send(AgentEvent::Usage {
+            input_tokens: response.total_usage.input_tokens,
+            output_tokens: response.total_usage.output_tokens,
+        });
+        Ok(response.output)
+    }
+
+    /// Stream a prompt with the shared conversation, reporting text and tool activity
+    /// through `events` as it arrives. The exchange (including tool calls and results)
+    /// is appended to `history`, even when the run fails or is cancelled part way through.
+    pub async fn stream_with_history(
+        &self,
+        input: &str,
+        history: &mut Vec<Message>,
+        depth: usize,
+        events: &EventSender,
+        cancel: &CancellationToken,
+    ) -> RunResult {
+        with_agent!(self, agent => {
+            let stream = agent
+                .stream_prompt(input)
+                .with_history(history.clone())
+                .multi_turn(depth)
+                .await;
+            collect_stream(stream, input, history, events, cancel).await
+        })
+    }
+}
+
+/// How a streamed run ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunOutcome {
+    Completed,
+    /// Cancelled by the user; the partial output is kept
+    Interrupted,
+    Failed(String),
+}
+
+impl RunOutcome {
+    /// completed, interrupted or failed, for machine-readable output
+    pub fn status(&self) -> &'static str {
+        match self {
+            RunOutcome::Completed => "completed",
+            RunOutcome::Interrupted => "interrupted",
+            RunOutcome::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Assistant text with tool activity interleaved, plus how the run ended
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub display: String,
+    pub outcome: RunOutcome,
+}
+
+impl RunResult {
+    pub fn completed(display: impl Into<String>) -> Self {
+        Self {
+            display: display.into(),
+            outcome: RunOutcome::Completed,
+        }
+    }
+
+    /// A request that couldn't run; the message is shown as the output
+    pub fn failed(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            outcome: RunOutcome::Failed(message.clone()),
+            display: message,
+        }
+    }
+}
+
+async fn collect_stream<R, E>(
+    mut stream: impl Stream<Item = Result<MultiTurnStreamItem<R>, E>> + Unpin,
+    input: &str,
+    history: &mut Vec<Message>,
+    events: &EventSender,
+    cancel: &CancellationToken,
+) -> RunResult
+where
+    E: std::fmt::Display,
+{
+    let mut turn = TurnRecorder::new(input);
+
+    // A closed receiver just means nobody is watching, so send errors are ignored
+    let outcome = loop {
+        // Dropping the stream on cancel aborts the in-flight request and any running tool
+        let item = tokio::select! {
+            item = stream.next() => item,
+            _ = cancel.cancelled() => break RunOutcome::Interrupted,
+        };
+
+        match item {
+            Some(Ok(MultiTurnStreamItem::StreamAssistantItem(StreamedAssistantContent::Text(text)))) => {
+                turn.text(&text.text);
+                let _ = events.send(AgentEvent::Text(text.text));
+            }
+            Some(Ok(MultiTurnStreamItem::StreamAssistantItem(StreamedAssistantContent::ToolCall(call)))) => {
+                let event = AgentEvent::ToolCall {
+                    name: call.function.name.clone(),
+                    arguments: call.function.arguments.clone(),
+                };
+                turn.tool_call(call);
+                let _ = events.send(event);
+            }
+            Some(Ok(MultiTurnStreamItem::StreamUserItem(StreamedUserContent::ToolResult(result)))) => {
+                let (name, result) = turn.tool_result(result);
+                let _ = events.send(AgentEvent::ToolResult { name, result });
+            }
+            Some(Ok(MultiTurnStreamItem::FinalResponse(response))) => {
+                let usage = response.usage();
+                let _ = events.send(AgentEvent::Usage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                });
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => break RunOutcome::Failed(e.to_string()),
+            None => break RunOutcome::Completed,
+        }
+    };
+
+    RunResult {
+        display: turn.finish(history),
+        outcome,
+    }
+}
+
+/// Rebuilds the model-facing messages of one turn from stream items
+struct TurnRecorder {
+    messages: Vec<Message>,
+    /// Assistant text and tool calls not yet flushed into a message
+    text: String,
+    calls: Vec<ToolCall>,
+    /// Tool names by call id, for labelling results
+    names: HashMap<String, String>,
+    /// Calls that have not produced a result yet
+    outstanding: HashSet<String>,
+    /// Text shown to the user, with tool activity interleaved
+    display: String,
+}
+
+impl TurnRecorder {
+    fn new(input: &str) -> Self {
+        Self {
+            messages: vec![Message::user(input)],
+            text: String::new(),
+            calls: Vec::new(),
+            names: HashMap::new(),
+            outstanding: HashSet::new(),
+            display: String::new(),
+        }
+    }
+
+    fn text(&mut self, chunk: &str) {
+        self.text.push_str(chunk);
+        self.display.push_str(chunk);
+    }
+
+    fn tool_call(&mut self, call: ToolCall) {
+        if !self.display.is_empty() && !self.display.ends_with('\n') {
+            self.display.push('\n');
+        }
+        self.display.push_str(&events::tool_call_label(&call.function.name, &call.function.arguments));
+        self.display.push('\n');
+
+        self.names.insert(call.id.clone(), call.function.name.clone());
+        self.outstanding.insert(call.id.clone());
+        self.calls.push(call);
+    }
+
+    fn tool_result(&mut self, result: ToolResult) -> (String, String) {
+        self.flush_assistant();
+
+        let name = self.names.get(&result.id).cloned().unwrap_or_default();
+        let text = result
+            .content
+            .iter()
+            .filter_map(|c| match c {
+                ToolResultContent::Text(t) => Some(t.text.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.display.push_str(&events::tool_result_preview(&text));
+        self.display.push('\n');
+
+        self.outstanding.remove(&result.id);
+        self.messages.push(Message::User {
+            content: OneOrMany::one(UserContent::ToolResult(result)),
+        });
+
+        (name, text)
+    }
+
+    fn flush_assistant(&mut self) {
+        let mut content = Vec::new();
+        if !self.text.is_empty() {
+            content.push(AssistantContent::text(std::mem::take(&mut self.text)));
+        }
+        content.extend(self.calls.drain(..).map(AssistantContent::ToolCall));
+
+        if let Ok(content) = OneOrMany::many(content) {
+            self.messages.push(Message::Assistant { id: None, content });
+        }
+    }
+
+    /// Append the turn to `history` and return the display text. Tool calls that never
+    /// got a result (the run failed or was cut short) are dropped, since providers
+    /// reject histories with unanswered tool calls.
+    fn finish(mut self, history: &mut Vec<Message>) -> String {
+        self.flush_assistant();
+
+        for message in self.messages {
+            let message = match message {
+                Message::Assistant { id, content } if !self.outstanding.is_empty() => {
+                    let kept: Vec<AssistantContent> = content
+                        .into_iter()
+                        .filter(|c| !matches!(c, AssistantContent::ToolCall(call) if self.outstanding.contains(&call.id)))
+                        .collect();
+                    match OneOrMany::many(kept) {
+                        Ok(content) => Message::Assistant { id, content },
+                        Err(_) => continue,
+                    }
+                }
+                message => message,
+            };
+            history.push(message);
+        }
+
+        self.display.trim_end().to_string()
+    }
+}
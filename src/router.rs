@@ -0,0 +1,176 @@
+use anyhow::{Context, Result, bail};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::agents::AgentRegistry;
+use crate::config::RoutingConfig;
+
+/// Split an explicit "@agent request" prefix into the agent name and the remaining request
+pub fn parse_override(input: &str) -> Option<(&str, &str)> {
+    let rest = input.strip_prefix('@')?;
+    let (name, request) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((name, request.trim()))
+}
+
+/// Built-in keyword rules per agent; patterns from [routing.patterns] are added to these
+const DEFAULT_PATTERNS: &[(&str, &[&str])] = &[
+    ("git", &[
+        r"\bgit\b",
+        r"\b(commit|rebase|stash|cherry-pick|checkout)\b",
+        r"\b(branch|branches)\b",
+    ]),
+    ("web", &[
+        r"https?://",
+        r"\b(fetch|download)\b.*\b(url|page|website|docs|documentation)\b",
+        r"\bwebsite\b",
+    ]),
+    ("execute", &[
+        r"^(run|execute|exec)\b",
+        r"\b(cargo|npm|yarn|make|pytest|go)\s+(build|test|run|check|install)\b",
+    ]),
+    ("code", &[
+        r"\b(grep|search|find)\b.*\b(code|functions?|structs?|class(es)?|usages?|references?|definitions?|todos?)\b",
+        r"\bwhere\b.*\b(defined|used|called|implemented|parsed)\b",
+        r"\bregex\b",
+    ]),
+    ("planner", &[
+        r",\s*(and\s+)?then\b",
+        r"\b(after that|afterwards|and finally)\b",
+    ]),
+    ("file", &[
+        r"\b(read|show|open|cat)\b.*\.\w{1,5}\b",
+        r"\b(edit|rename|delete|remove|move|copy|create|write)\b.*\b(files?|directory|directories|folders?)\b",
+        r"\b(list|tree)\b.*\b(files|directory|directories|folders?)\b",
+    ]),
+];
+
+/// How a request was routed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum RouteSource {
+    /// Keyword rules were confident enough on their own
+    Rules { confidence: f32, pattern: String },
+    /// Fell back to the LLM intent classifier
+    Classifier,
+    /// Explicit @agent prefix
+    Override,
+    /// Pinned with /agent <name>
+    Pinned,
+}
+
+impl std::fmt::Display for RouteSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RouteSource::Rules { confidence, .. } => write!(f, "rules {:.2}", confidence),
+            RouteSource::Classifier => f.write_str("classifier"),
+            RouteSource::Override => f.write_str("override"),
+            RouteSource::Pinned => f.write_str("pinned"),
+        }
+    }
+}
+
+/// Routing decision for one request
+#[derive(Debug, Clone)]
+pub struct RouteDecision {
+    /// Name of the agent that gets the request
+    pub agent: String,
+    pub source: RouteSource,
+}
+
+struct AgentRules {
+    agent: String,
+    patterns: Vec<Regex>,
+}
+
+/// Deterministic first-pass router that runs before the LLM classifier
+pub struct Router {
+    enabled: bool,
+    threshold: f32,
+    rules: Vec<AgentRules>,
+}
+
+impl Router {
+    pub fn new(config: &RoutingConfig, agents: &AgentRegistry) -> Result<Self> {
+        let compile = |pattern: &str| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("Invalid routing pattern: {}", pattern))
+        };
+
+        let mut rules: Vec<AgentRules> = Vec::new();
+        for (agent, patterns) in DEFAULT_PATTERNS {
+            // Built-in agents can't be removed, but keep the router honest if one is missing
+            if agents.get(agent).is_none() {
+                continue;
+            }
+            rules.push(AgentRules {
+                agent: agent.to_string(),
+                patterns: patterns.iter().map(|p| compile(p)).collect::<Result<_>>()?,
+            });
+        }
+
+        // Sort user keys so rule order doesn't depend on HashMap iteration. Keys may
+        // use the older intent labels (code_search, file_ops, ...) as well as agent names.
+        let mut custom: Vec<_> = config.patterns.iter().collect();
+        custom.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, patterns) in custom {
+            let Some(specialist) = agents.get(key) else {
+                bail!("routing.patterns: unknown agent '{}' (available: {})", key, agents.names());
+            };
+            let agent = &specialist.spec.name;
+            let compiled = patterns.iter().map(|p| compile(p)).collect::<Result<Vec<_>>>()?;
+            match rules.iter_mut().find(|r| &r.agent == agent) {
+                Some(existing) => existing.patterns.extend(compiled),
+                None => rules.push(AgentRules {
+                    agent: agent.clone(),
+                    patterns: compiled,
+                }),
+            }
+        }
+
+        Ok(Self {
+            enabled: config.enabled,
+            threshold: config.confidence_threshold,
+            rules,
+        })
+    }
+
+    /// Route by rules alone; `None` means confidence was too low and the
+    /// LLM classifier should decide
+    pub fn route(&self, input: &str) -> Option<RouteDecision> {
+        if !self.enabled {
+            return None;
+        }
+
+        // Count matching patterns per agent, remembering the first match for the record
+        let mut scores: Vec<(&str, usize, &str)> = self
+            .rules
+            .iter()
+            .filter_map(|rules| {
+                let matched: Vec<&Regex> = rules.patterns.iter().filter(|p| p.is_match(input)).collect();
+                matched
+                    .first()
+                    .map(|first| (rules.agent.as_str(), matched.len(), first.as_str()))
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let (agent, best, pattern) = *scores.first()?;
+        let runner_up = scores.get(1).map_or(0, |s| s.1);
+
+        // Margin over the runner-up; a single hit on its own is only a weak signal
+        let mut confidence = (best - runner_up) as f32 / best as f32;
+        if best == 1 {
+            confidence *= 0.75;
+        }
+
+        (confidence >= self.threshold).then(|| RouteDecision {
+            agent: agent.to_string(),
+            source: RouteSource::Rules {
+                confidence,
+                pattern: pattern.to_string(),
+            },
+        })
+    }
+}
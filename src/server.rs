@@ -0,0 +1,364 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, broadcast, oneshot};
+use tokio_util::sync::CancellationToken;
+
+use crate::approval::ApprovalDecision;
+use crate::events::AgentEvent;
+use crate::provider::RunOutcome;
+use crate::session::Session;
+use crate::{ActiveRequest, Ada};
+
+/// Events buffered per session; a subscriber that falls further behind skips ahead
+const EVENT_BUFFER: usize = 256;
+
+/// One conversation driven over HTTP
+struct ServerSession {
+    ada: Arc<Ada>,
+    /// Events of every request in the session, fanned out to SSE subscribers
+    events: broadcast::Sender<Value>,
+    /// Approval requests waiting for an answer, by id
+    pending: std::sync::Mutex<HashMap<u64, Value>>,
+    /// Cancels the running request; None when idle
+    running: std::sync::Mutex<Option<CancellationToken>>,
+    /// Messages in one session run one at a time
+    busy: AtomicBool,
+}
+
+impl ServerSession {
+    fn pending(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Value>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn running(&self) -> std::sync::MutexGuard<'_, Option<CancellationToken>> {
+        self.running.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Clone)]
+struct ServerState {
+    /// Instance new sessions are built from
+    ada: Arc<Ada>,
+    sessions: Arc<Mutex<HashMap<String, Arc<ServerSession>>>>,
+}
+
+impl ServerState {
+    async fn session(&self, id: &str) -> Result<Arc<ServerSession>, ApiError> {
+        self.sessions
+            .lock()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("No session '{}'", id)))
+    }
+}
+
+/// An error response: {"error": "..."}
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self(status, message.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateSession {
+    /// Reopen a saved session instead of starting a new one
+    resume: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PostMessage {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct ApprovalAnswer {
+    /// once, session or deny
+    decision: String,
+    reason: Option<String>,
+}
+
+/// Serve the HTTP API until Ctrl+C. Every session gets its own instance built from
+/// `ada`, so routing, tools and approvals behave as in the TUI.
+pub async fn serve(ada: Ada, host: IpAddr, port: u16) -> Result<()> {
+    let state = ServerState {
+        ada: Arc::new(ada),
+        sessions: Arc::default(),
+    };
+
+    let mut app = Router::new()
+        .route("/sessions", get(list_sessions).post(create_session))
+        .route("/sessions/:id/messages", post(post_message))
+        .route("/sessions/:id/cancel", post(cancel))
+        .route("/sessions/:id/events", get(events))
+        .route("/sessions/:id/approvals", get(list_approvals))
+        .route("/sessions/:id/approvals/:approval", post(answer_approval))
+        .route("/sessions/:id/transcript", get(transcript));
+    if host.is_loopback() {
+        app = app.layer(middleware::from_fn(check_host));
+    }
+    let app = app.with_state(state);
+
+    let address = SocketAddr::new(host, port);
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to listen on {}", address))?;
+    if !host.is_loopback() {
+        eprintln!("Warning: anyone who can reach {} can run tools on this machine", address);
+    }
+    eprintln!("Ada API listening on http://{}", listener.local_addr()?);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+        .context("Server failed")
+}
+
+/// Refuse requests addressed to another host name, so a web page can't reach the
+/// API through DNS rebinding
+async fn check_host(request: Request, next: Next) -> Response {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+
+    if matches!(name, "localhost" | "127.0.0.1" | "::1") {
+        next.run(request).await
+    } else {
+        ApiError::new(StatusCode::FORBIDDEN, format!("Host '{}' is not allowed", host)).into_response()
+    }
+}
+
+/// POST /sessions: start a session, or reopen a saved one with {"resume": "<id>"}
+async fn create_session(
+    State(state): State<ServerState>,
+    body: Option<Json<CreateSession>>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let session = match body.and_then(|Json(body)| body.resume) {
+        Some(id) => Session::load(&id).map_err(|e| ApiError::new(StatusCode::NOT_FOUND, e.to_string()))?,
+        None => Session::new(),
+    };
+    let ada = state
+        .ada
+        .for_session(session)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    let (id, _) = ada.session_snapshot().await;
+
+    // Never replace an open session: both would write to the same session file
+    let mut sessions = state.sessions.lock().await;
+    if sessions.contains_key(&id) {
+        return Err(ApiError::new(StatusCode::CONFLICT, format!("Session {} is already open", id)));
+    }
+
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    sessions.insert(
+        id.clone(),
+        Arc::new(ServerSession {
+            ada: Arc::new(ada),
+            events,
+            pending: Default::default(),
+            running: Default::default(),
+            busy: AtomicBool::new(false),
+        }),
+    );
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
+}
+
+/// GET /sessions: the sessions this server has open
+async fn list_sessions(State(state): State<ServerState>) -> Json<Value> {
+    let sessions = state.sessions.lock().await;
+    let mut listing = Vec::new();
+    for (id, session) in sessions.iter() {
+        let mut item = json!({ "id": id, "busy": session.busy.load(Ordering::SeqCst) });
+        // A running request holds the session; skip the details rather than wait
+        if let Ok(conversation) = session.ada.session.try_lock() {
+            item["title"] = json!(conversation.title());
+            item["turns"] = json!(conversation.turns());
+        }
+        listing.push(item);
+    }
+    Json(json!({ "sessions": listing }))
+}
+
+/// POST /sessions/:id/messages: run a message through routing and the agents and
+/// return the result. Progress and approval requests stream on /events meanwhile.
+async fn post_message(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Json(body): Json<PostMessage>,
+) -> Result<Json<Value>, ApiError> {
+    // Switching sessions in place would leave this one keyed by the wrong id and
+    // could open a session that is already open under its own id
+    if body.content.trim().starts_with("/sessions ") {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "Open another session with POST /sessions and {\"resume\": \"<id>\"}",
+        ));
+    }
+
+    let session = state.session(&id).await?;
+    if session.busy.swap(true, Ordering::SeqCst) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "A message is already being processed in this session",
+        ));
+    }
+
+    // The request runs on its own task, so it finishes even if the client hangs up
+    let (done, result) = oneshot::channel();
+    tokio::spawn(run_message(session, body.content, done));
+    let result = result
+        .await
+        .map_err(|_| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "The request task ended unexpectedly"))?;
+    Ok(Json(result))
+}
+
+async fn run_message(session: Arc<ServerSession>, content: String, done: oneshot::Sender<Value>) {
+    let mut request = ActiveRequest::start(Arc::clone(&session.ada), content);
+    *session.running() = Some(request.cancel.clone());
+
+    let result = loop {
+        tokio::select! {
+            // Events first, so none are left behind when the task finishes
+            biased;
+            Some(event) = request.events.recv() => {
+                let value = event.to_json();
+                if let AgentEvent::ApprovalRequested { id, .. } = &event {
+                    session.pending().insert(*id, value.clone());
+                }
+                let _ = session.events.send(value);
+            }
+            result = &mut request.task => break result,
+        }
+    };
+
+    let result = match result {
+        Ok(result) => {
+            let error = match &result.outcome {
+                RunOutcome::Failed(e) => Some(e.clone()),
+                _ => None,
+            };
+            json!({ "type": "result", "status": result.outcome.status(), "display": result.display, "error": error })
+        }
+        Err(e) => json!({ "type": "result", "status": "failed", "display": "", "error": format!("Agent task failed: {}", e) }),
+    };
+
+    session.pending().clear();
+    *session.running() = None;
+    session.busy.store(false, Ordering::SeqCst);
+    let _ = session.events.send(result.clone());
+    let _ = done.send(result);
+}
+
+/// POST /sessions/:id/cancel: interrupt the running request
+async fn cancel(State(state): State<ServerState>, Path(id): Path<String>) -> Result<Json<Value>, ApiError> {
+    let session = state.session(&id).await?;
+    let cancelled = match session.running().as_ref() {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    };
+    Ok(Json(json!({ "cancelled": cancelled })))
+}
+
+/// GET /sessions/:id/events: server-sent events for every request in the session,
+/// named by type (routed, text, tool_call, tool_result, approval_requested, usage, result)
+async fn events(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let session = state.session(&id).await?;
+    let stream = futures::stream::unfold(session.events.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(value) => {
+                    let kind = value["type"].as_str().unwrap_or("event").to_string();
+                    let event = Event::default().event(kind).data(value.to_string());
+                    return Some((Ok(event), receiver));
+                }
+                // A slow client misses events rather than holding up the agent
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// GET /sessions/:id/approvals: tool calls waiting for an answer
+async fn list_approvals(State(state): State<ServerState>, Path(id): Path<String>) -> Result<Json<Value>, ApiError> {
+    let session = state.session(&id).await?;
+    let pending = session.pending();
+    let mut approvals: Vec<(&u64, &Value)> = pending.iter().collect();
+    approvals.sort_by_key(|(id, _)| **id);
+    let approvals: Vec<&Value> = approvals.into_iter().map(|(_, approval)| approval).collect();
+    Ok(Json(json!({ "approvals": approvals })))
+}
+
+/// POST /sessions/:id/approvals/:approval: answer with {"decision": "once" | "session" | "deny", "reason": "..."}
+async fn answer_approval(
+    State(state): State<ServerState>,
+    Path((id, approval)): Path<(String, u64)>,
+    Json(body): Json<ApprovalAnswer>,
+) -> Result<Json<Value>, ApiError> {
+    let session = state.session(&id).await?;
+    let decision = match body.decision.as_str() {
+        "once" => ApprovalDecision::Once,
+        "session" => ApprovalDecision::Session,
+        "deny" => ApprovalDecision::Deny(body.reason.unwrap_or_default()),
+        other => {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Unknown decision '{}' (expected once, session or deny)", other),
+            ));
+        }
+    };
+
+    if !session.ada.tools.approvals().resolve(approval, decision) {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("No pending approval {}", approval),
+        ));
+    }
+    session.pending().remove(&approval);
+    Ok(Json(json!({ "resolved": approval })))
+}
+
+/// GET /sessions/:id/transcript: every entry so far; waits for a running request to finish
+async fn transcript(State(state): State<ServerState>, Path(id): Path<String>) -> Result<Json<Value>, ApiError> {
+    let session = state.session(&id).await?;
+    let (id, entries) = session.ada.session_snapshot().await;
+    Ok(Json(json!({ "id": id, "entries": entries })))
+}
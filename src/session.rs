@@ -0,0 +1,296 @@
+use anyhow::{Context, Result, bail};
+use rig::completion::Message;
+use rig::completion::message::{AssistantContent, ToolResultContent, UserContent};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// One line of the visible transcript, plus the routing and tool activity behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEntry {
+    User { content: String },
+    Assistant { content: String },
+    System { content: String },
+    Route {
+        intent: String,
+        agent: String,
+        /// Which path made the decision ("rules 0.75", "classifier"); empty in older sessions
+        #[serde(default)]
+        via: String,
+    },
+    ToolCall {
+        name: String,
+        arguments: serde_json::Value,
+        result: Option<String>,
+    },
+}
+
+/// A conversation persisted under ~/.ada/sessions/<id>.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub cwd: String,
+    #[serde(default)]
+    pub entries: Vec<SessionEntry>,
+    /// Model-facing history shared by every agent
+    #[serde(default)]
+    pub history: Vec<Message>,
+    /// Agent all requests go to, set with /agent <name>
+    #[serde(default)]
+    pub pinned_agent: Option<String>,
+    /// Agent from --agent, which wins over `pinned_agent` for this run only
+    #[serde(skip)]
+    pub run_agent: Option<String>,
+}
+
+/// Lightweight view of a stored session for listings
+pub struct SessionSummary {
+    pub id: String,
+    pub updated_at: u64,
+    pub cwd: String,
+    pub title: String,
+    pub turns: usize,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        let now = now_secs();
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let cwd = std::env::current_dir()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+
+        // Random suffix so sessions started in the same millisecond get different ids
+        let suffix = RandomState::new().build_hasher().finish() as u32;
+
+        Self {
+            id: format!("{:x}-{:08x}", millis, suffix),
+            created_at: now,
+            updated_at: now,
+            cwd,
+            entries: Vec::new(),
+            history: Vec::new(),
+            pinned_agent: None,
+            run_agent: None,
+        }
+    }
+
+    /// Get the sessions directory path (~/.ada/sessions)
+    pub fn sessions_dir() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("sessions"))
+    }
+
+    fn path_for(id: &str) -> Result<PathBuf> {
+        Ok(Self::sessions_dir()?.join(format!("{}.json", id)))
+    }
+
+    /// Full id of the stored session matching an id or unique id prefix
+    fn resolve_id(id: &str) -> Result<String> {
+        let matches: Vec<SessionSummary> = Self::list()?
+            .into_iter()
+            .filter(|s| s.id.starts_with(id))
+            .collect();
+
+        match matches.as_slice() {
+            [] => bail!("No session matching '{}'", id),
+            [only] => Ok(only.id.clone()),
+            _ => bail!("Session id '{}' is ambiguous ({} matches)", id, matches.len()),
+        }
+    }
+
+    /// Load a session by id or unique id prefix
+    pub fn load(id: &str) -> Result<Self> {
+        let id = Self::resolve_id(id)?;
+        let contents = fs::read_to_string(Self::path_for(&id)?)
+            .with_context(|| format!("Failed to read session {}", id))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse session {}", id))
+    }
+
+    /// Load the most recently updated session
+    pub fn latest() -> Result<Self> {
+        match Self::list()?.first() {
+            Some(summary) => Self::load(&summary.id),
+            None => bail!("No saved sessions in {}", Self::sessions_dir()?.display()),
+        }
+    }
+
+    /// All stored sessions, most recently updated first
+    pub fn list() -> Result<Vec<SessionSummary>> {
+        let dir = Self::sessions_dir()?;
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions = Vec::new();
+        for entry in fs::read_dir(&dir).context("Failed to read sessions directory")?.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            // Skip unreadable files instead of failing the whole listing
+            let Ok(contents) = fs::read_to_string(&path) else { continue };
+            let Ok(session) = serde_json::from_str::<Session>(&contents) else { continue };
+
+            sessions.push(SessionSummary {
+                title: session.title(),
+                turns: session.turns(),
+                id: session.id,
+                updated_at: session.updated_at,
+                cwd: session.cwd,
+            });
+        }
+
+        sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(sessions)
+    }
+
+    /// Delete a stored session by id or unique id prefix, returning its full id
+    pub fn delete(id: &str) -> Result<String> {
+        let id = Self::resolve_id(id)?;
+        fs::remove_file(Self::path_for(&id)?).with_context(|| format!("Failed to delete session {}", id))?;
+        Ok(id)
+    }
+
+    /// Write the session to disk (sessions without any user turn are not stored)
+    pub fn save(&mut self) -> Result<()> {
+        if self.turns() == 0 {
+            return Ok(());
+        }
+
+        let dir = Self::sessions_dir()?;
+        fs::create_dir_all(&dir).context("Failed to create sessions directory")?;
+
+        self.updated_at = now_secs();
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize session")?;
+        fs::write(Self::path_for(&self.id)?, json).context("Failed to write session file")?;
+
+        Ok(())
+    }
+
+    pub fn push(&mut self, entry: SessionEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Record the tool calls and results found in history messages added this turn
+    pub fn record_tool_calls(&mut self, from: usize) {
+        let first_new_entry = self.entries.len();
+
+        for message in self.history.iter().skip(from) {
+            match message {
+                Message::Assistant { content, .. } => {
+                    for item in content.iter() {
+                        if let AssistantContent::ToolCall(call) = item {
+                            self.entries.push(SessionEntry::ToolCall {
+                                name: call.function.name.clone(),
+                                arguments: call.function.arguments.clone(),
+                                result: None,
+                            });
+                        }
+                    }
+                }
+                Message::User { content } => {
+                    for item in content.iter() {
+                        if let UserContent::ToolResult(tool_result) = item {
+                            let text = tool_result
+                                .content
+                                .iter()
+                                .filter_map(|c| match c {
+                                    ToolResultContent::Text(t) => Some(t.text.clone()),
+                                    _ => None,
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n");
+
+                            // Results come back in call order; attach to the first pending call
+                            let pending = self.entries[first_new_entry..].iter_mut().find(|e| {
+                                matches!(e, SessionEntry::ToolCall { result: None, .. })
+                            });
+                            if let Some(SessionEntry::ToolCall { result, .. }) = pending {
+                                *result = Some(text);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// First user message, used as the session title in listings
+    pub fn title(&self) -> String {
+        let first = self.entries.iter().find_map(|e| match e {
+            SessionEntry::User { content } => Some(content.as_str()),
+            _ => None,
+        });
+
+        match first {
+            Some(text) if text.chars().count() > 60 => {
+                format!("{}…", text.chars().take(60).collect::<String>())
+            }
+            Some(text) => text.to_string(),
+            None => "(empty)".to_string(),
+        }
+    }
+
+    /// Plain-text transcript for printing outside the TUI
+    pub fn transcript(&self) -> String {
+        let mut output = format!(
+            "Session {} ({} turns, {})\n{}\n",
+            self.id,
+            self.turns(),
+            format_age(self.updated_at),
+            self.cwd
+        );
+        for entry in &self.entries {
+            match entry {
+                SessionEntry::User { content } => output.push_str(&format!("\n> {}\n", content)),
+                SessionEntry::Assistant { content } => output.push_str(&format!("\n{}\n", content)),
+                SessionEntry::System { content } => output.push_str(&format!("\n[{}]\n", content)),
+                SessionEntry::Route { intent, agent, .. } => output.push_str(&format!("\n[@{} ({})]\n", intent, agent)),
+                SessionEntry::ToolCall { name, arguments, .. } => output.push_str(&format!("  ⚙ {} {}\n", name, arguments)),
+            }
+        }
+        output.trim_end().to_string()
+    }
+
+    /// Agent requests go to instead of being routed, if any
+    pub fn pinned(&self) -> Option<&str> {
+        self.run_agent.as_deref().or(self.pinned_agent.as_deref())
+    }
+
+    pub fn turns(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e, SessionEntry::User { .. }))
+            .count()
+    }
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Human-friendly age of a unix timestamp ("5m ago", "3h ago", "2d ago")
+pub fn format_age(timestamp: u64) -> String {
+    let elapsed = now_secs().saturating_sub(timestamp);
+    match elapsed {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", elapsed / 60),
+        3600..=86399 => format!("{}h ago", elapsed / 3600),
+        _ => format!("{}d ago", elapsed / 86400),
+    }
+}
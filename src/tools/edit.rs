@@ -1,11 +1,13 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use tokio::fs;
 
 use super::formatter::{create_diff, ToolOutput};
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
 
 #[derive(Deserialize)]
 pub struct EditArgs {
@@ -18,6 +20,56 @@ pub struct EditArgs {
 #[derive(Deserialize, Serialize)]
 pub struct Edit;
 
+impl ToolInfo for Edit {
+    const CATEGORY: ToolCategory = ToolCategory::Files;
+    const MUTATES: bool = true;
+    const DESCRIPTION: &'static str = "Replace text in a file by finding and replacing exact strings";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The path to the file to edit"
+                },
+                "old_string": {
+                    "type": "string",
+                    "description": "The exact string to find and replace"
+                },
+                "new_string": {
+                    "type": "string",
+                    "description": "The new string to replace with"
+                },
+                "replace_all": {
+                    "type": "boolean",
+                    "description": "If true, replace all occurrences. If false, only replace first occurrence. Default: false"
+                }
+            },
+            "required": ["file_path", "old_string", "new_string"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.file_path.clone()]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.file_path = workspace.anchor(&args.file_path);
+    }
+
+    async fn preview(&self, args: &Self::Args) -> Option<String> {
+        let old_content = fs::read_to_string(&args.file_path).await.ok()?;
+        let new_content = if args.replace_all.unwrap_or(false) {
+            old_content.replace(&args.old_string, &args.new_string)
+        } else {
+            old_content.replacen(&args.old_string, &args.new_string, 1)
+        };
+        let diff = create_diff(&args.file_path, &old_content, &new_content, 2);
+        Some(ToolOutput::new("Edit", &args.file_path).with_diff(diff).format())
+    }
+}
+
 impl Tool for Edit {
     const NAME: &'static str = "edit";
 
@@ -26,33 +78,7 @@ impl Tool for Edit {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "edit".to_string(),
-            description: "Replace text in a file by finding and replacing exact strings"
-                .to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "file_path": {
-                        "type": "string",
-                        "description": "The path to the file to edit"
-                    },
-                    "old_string": {
-                        "type": "string",
-                        "description": "The exact string to find and replace"
-                    },
-                    "new_string": {
-                        "type": "string",
-                        "description": "The new string to replace with"
-                    },
-                    "replace_all": {
-                        "type": "boolean",
-                        "description": "If true, replace all occurrences. If false, only replace first occurrence. Default: false"
-                    }
-                },
-                "required": ["file_path", "old_string", "new_string"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
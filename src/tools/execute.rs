@@ -1,10 +1,12 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use tokio::process::Command;
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
 
 #[derive(Deserialize)]
 pub struct ExecuteArgs {
@@ -15,6 +17,56 @@ pub struct ExecuteArgs {
 #[derive(Deserialize, Serialize)]
 pub struct Execute;
 
+/// Precedes the status in the output of a command that exited unsuccessfully
+const EXIT_CODE: &str = "\nExit code: ";
+
+impl Execute {
+    /// Whether `output` is that of a command that exited unsuccessfully
+    pub fn failed(output: &str) -> bool {
+        output.contains(EXIT_CODE)
+    }
+}
+
+impl ToolInfo for Execute {
+    const CATEGORY: ToolCategory = ToolCategory::Shell;
+    const MUTATES: bool = true;
+    const DESCRIPTION: &'static str = "Execute a shell command and return its output";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "The shell command to execute"
+                },
+                "working_dir": {
+                    "type": "string",
+                    "description": "Optional working directory for the command"
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        args.working_dir.iter().cloned().collect()
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        // Without a working directory the command runs in the root
+        let dir = workspace.anchor(args.working_dir.as_deref().unwrap_or("."));
+        args.working_dir = Some(dir).filter(|dir| dir != ".");
+    }
+
+    async fn preview(&self, args: &Self::Args) -> Option<String> {
+        match &args.working_dir {
+            Some(dir) => Some(format!("$ {}\n(in {})", args.command, dir)),
+            None => Some(format!("$ {}", args.command)),
+        }
+    }
+}
+
 impl Tool for Execute {
     const NAME: &'static str = "execute";
 
@@ -23,30 +75,16 @@ impl Tool for Execute {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "execute".to_string(),
-            description: "Execute a shell command and return its output".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "command": {
-                        "type": "string",
-                        "description": "The shell command to execute"
-                    },
-                    "working_dir": {
-                        "type": "string",
-                        "description": "Optional working directory for the command"
-                    }
-                },
-                "required": ["command"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         let mut cmd = Command::new("sh");
         cmd.arg("-c").arg(&args.command);
 
+        // Kill the child if the agent run is interrupted while it's still running
+        cmd.kill_on_drop(true);
+
         if let Some(working_dir) = args.working_dir {
             cmd.current_dir(working_dir);
         }
@@ -72,7 +110,7 @@ impl Tool for Execute {
         }
 
         if !output.status.success() {
-            result.push_str(&format!("\nExit code: {}", output.status));
+            result.push_str(&format!("{}{}", EXIT_CODE, output.status));
         }
 
         if result.is_empty() {
@@ -1,11 +1,13 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use tokio::fs;
 
 use super::formatter::{create_diff, ToolOutput};
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
 
 #[derive(Deserialize)]
 pub struct FileOpsArgs {
@@ -17,6 +19,57 @@ pub struct FileOpsArgs {
 #[derive(Deserialize, Serialize)]
 pub struct FileOps;
 
+impl ToolInfo for FileOps {
+    const CATEGORY: ToolCategory = ToolCategory::Files;
+    const MUTATES: bool = true;
+    const DESCRIPTION: &'static str = "Perform file operations: delete, move, rename, copy";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "description": "Operation to perform: 'delete', 'move', 'copy'"
+                },
+                "source": {
+                    "type": "string",
+                    "description": "Source file or directory path"
+                },
+                "destination": {
+                    "type": "string",
+                    "description": "Destination path (required for move/copy operations)"
+                }
+            },
+            "required": ["operation", "source"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        std::iter::once(args.source.clone()).chain(args.destination.clone()).collect()
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.source = workspace.anchor(&args.source);
+        args.destination = args.destination.as_deref().map(|d| workspace.anchor(d));
+    }
+
+    async fn preview(&self, args: &Self::Args) -> Option<String> {
+        let destination = args.destination.as_deref().unwrap_or("?");
+        let is_dir = fs::metadata(&args.source).await.is_ok_and(|m| m.is_dir());
+        let description = match args.operation.as_str() {
+            "delete" if is_dir => {
+                format!("Delete directory {} and everything in it", args.source)
+            }
+            "delete" => format!("Delete {}", args.source),
+            "move" | "rename" => format!("Move {} to {}", args.source, destination),
+            "copy" => format!("Copy {} to {}", args.source, destination),
+            other => format!("{} {}", other, args.source),
+        };
+        Some(description)
+    }
+}
+
 impl Tool for FileOps {
     const NAME: &'static str = "file_ops";
 
@@ -25,28 +78,7 @@ impl Tool for FileOps {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "file_ops".to_string(),
-            description: "Perform file operations: delete, move, rename, copy".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "operation": {
-                        "type": "string",
-                        "description": "Operation to perform: 'delete', 'move', 'copy'"
-                    },
-                    "source": {
-                        "type": "string",
-                        "description": "Source file or directory path"
-                    },
-                    "destination": {
-                        "type": "string",
-                        "description": "Destination path (required for move/copy operations)"
-                    }
-                },
-                "required": ["operation", "source"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
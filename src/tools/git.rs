@@ -1,10 +1,17 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
+use std::path::PathBuf;
 use tokio::process::Command;
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
+
+/// Options whose value is a path, given as `--opt=<path>` (the `--opt <path>` form
+/// is caught as a plain argument)
+const PATH_OPTIONS: &[&str] = &["--git-dir", "--work-tree", "--output"];
 
 #[derive(Deserialize)]
 pub struct GitArgs {
@@ -13,7 +20,72 @@ pub struct GitArgs {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct Git;
+pub struct Git {
+    /// Workspace root, where git runs and relative paths are taken from
+    root: PathBuf,
+}
+
+impl Git {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            root: workspace.root().to_path_buf(),
+        }
+    }
+}
+
+impl ToolInfo for Git {
+    const CATEGORY: ToolCategory = ToolCategory::Git;
+    const MUTATES: bool = true;
+    const DESCRIPTION: &'static str = "Execute git operations (status, diff, log, add, commit, etc.)";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "description": "Git operation to perform (status, diff, log, add, commit, etc.)"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Additional arguments for the git command"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    fn call_mutates(&self, args: &Self::Args) -> bool {
+        // Inspection commands are safe to run without asking, unless told to write
+        // their output to a file (git diff --output=<path>) or run an external diff
+        let writes = args
+            .args
+            .iter()
+            .flatten()
+            .any(|arg| arg.starts_with("--output") || arg.starts_with("-o") || arg == "--ext-diff");
+        writes || !matches!(args.operation.as_str(), "status" | "diff" | "log" | "show" | "blame")
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        // Git arguments mix revisions, paths and option values. Every plain argument
+        // is reported: revisions resolve inside the root and never count as outside,
+        // while /etc/passwd in `git diff --no-index /etc/passwd /dev/null` or the
+        // directory of `-C <dir>` does.
+        let argv = std::iter::once(&args.operation).chain(args.args.iter().flatten());
+        argv.filter_map(|arg| match arg.split_once('=') {
+            Some((option, value)) if PATH_OPTIONS.contains(&option) => Some(value.to_string()),
+            _ if arg.starts_with('-') => None,
+            _ => Some(arg.clone()),
+        })
+        .collect()
+    }
+
+    async fn preview(&self, args: &Self::Args) -> Option<String> {
+        let extra = args.args.as_deref().unwrap_or_default().join(" ");
+        Some(format!("$ git {} {}", args.operation, extra).trim_end().to_string())
+    }
+}
 
 impl Tool for Git {
     const NAME: &'static str = "git";
@@ -23,30 +95,14 @@ impl Tool for Git {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "git".to_string(),
-            description: "Execute git operations (status, diff, log, add, commit, etc.)".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "operation": {
-                        "type": "string",
-                        "description": "Git operation to perform (status, diff, log, add, commit, etc.)"
-                    },
-                    "args": {
-                        "type": "array",
-                        "items": { "type": "string" },
-                        "description": "Additional arguments for the git command"
-                    }
-                },
-                "required": ["operation"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         let mut cmd = Command::new("git");
         cmd.arg(&args.operation);
+        cmd.current_dir(&self.root);
+        cmd.kill_on_drop(true);
 
         if let Some(extra_args) = args.args {
             cmd.args(&extra_args);
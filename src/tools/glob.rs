@@ -3,9 +3,12 @@ use ignore::WalkBuilder;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::{self, Workspace};
 
 #[derive(Deserialize)]
 pub struct GlobArgs {
@@ -14,7 +17,49 @@ pub struct GlobArgs {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct Glob;
+pub struct Glob {
+    /// Workspace root; symlinks found while walking must stay inside it
+    root: PathBuf,
+}
+
+impl Glob {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            root: workspace.root().to_path_buf(),
+        }
+    }
+}
+
+impl ToolInfo for Glob {
+    const CATEGORY: ToolCategory = ToolCategory::Search;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "Find files matching a glob pattern (e.g., '*.rs', '**/*.toml', 'src/**/*.rs')";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "The glob pattern to match (e.g., '*.rs', '**/*.toml')"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory to search in (default: current directory)"
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.path.clone().unwrap_or_else(|| ".".to_string())]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.path = Some(workspace.anchor(args.path.as_deref().unwrap_or(".")));
+    }
+}
 
 impl Tool for Glob {
     const NAME: &'static str = "glob";
@@ -24,24 +69,7 @@ impl Tool for Glob {
     type Output = Vec<String>;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "glob".to_string(),
-            description: "Find files matching a glob pattern (e.g., '*.rs', '**/*.toml', 'src/**/*.rs')".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "pattern": {
-                        "type": "string",
-                        "description": "The glob pattern to match (e.g., '*.rs', '**/*.toml')"
-                    },
-                    "path": {
-                        "type": "string",
-                        "description": "Directory to search in (default: current directory)"
-                    }
-                },
-                "required": ["pattern"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
@@ -58,6 +86,7 @@ impl Tool for Glob {
 
         let mut results = Vec::new();
 
+        let start = Path::new(search_path).canonicalize().unwrap_or_else(|_| self.root.clone());
         for entry_result in WalkBuilder::new(search_path)
             .git_ignore(true)
             .git_global(true)
@@ -66,6 +95,9 @@ impl Tool for Glob {
             .build()
         {
             let entry = entry_result.map_err(|e| ToolError(format!("Walk error: {}", e)))?;
+            if workspace::escapes(&self.root, &start, &entry) {
+                continue;
+            }
             let path = entry.path();
 
             if path.is_file() && glob_set.is_match(path) {
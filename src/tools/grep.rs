@@ -3,10 +3,13 @@ use regex::Regex;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::{self, Workspace};
 
 #[derive(Deserialize)]
 pub struct GrepArgs {
@@ -16,7 +19,53 @@ pub struct GrepArgs {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct Grep;
+pub struct Grep {
+    /// Workspace root; symlinks found while walking must stay inside it
+    root: PathBuf,
+}
+
+impl Grep {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            root: workspace.root().to_path_buf(),
+        }
+    }
+}
+
+impl ToolInfo for Grep {
+    const CATEGORY: ToolCategory = ToolCategory::Search;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "Search for a pattern in files (regex supported)";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "The regex pattern to search for"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File or directory to search in (default: current directory)"
+                },
+                "case_insensitive": {
+                    "type": "boolean",
+                    "description": "Case insensitive search (default: false)"
+                }
+            },
+            "required": ["pattern"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.path.clone().unwrap_or_else(|| ".".to_string())]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.path = Some(workspace.anchor(args.path.as_deref().unwrap_or(".")));
+    }
+}
 
 impl Tool for Grep {
     const NAME: &'static str = "grep";
@@ -26,28 +75,7 @@ impl Tool for Grep {
     type Output = Vec<String>;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "grep".to_string(),
-            description: "Search for a pattern in files (regex supported)".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "pattern": {
-                        "type": "string",
-                        "description": "The regex pattern to search for"
-                    },
-                    "path": {
-                        "type": "string",
-                        "description": "File or directory to search in (default: current directory)"
-                    },
-                    "case_insensitive": {
-                        "type": "boolean",
-                        "description": "Case insensitive search (default: false)"
-                    }
-                },
-                "required": ["pattern"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
@@ -80,6 +108,7 @@ impl Tool for Grep {
             }
         } else {
             // Search directory
+            let start = Path::new(search_path).canonicalize().unwrap_or_else(|_| self.root.clone());
             for entry_result in WalkBuilder::new(search_path)
                 .git_ignore(true)
                 .git_global(true)
@@ -88,6 +117,9 @@ impl Tool for Grep {
                 .build()
             {
                 let entry = entry_result.map_err(|e| ToolError(format!("Walk error: {}", e)))?;
+                if workspace::escapes(&self.root, &start, &entry) {
+                    continue;
+                }
                 let entry_path = entry.path();
 
                 if entry_path.is_file() {
@@ -1,10 +1,12 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use tokio::fs;
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
 
 #[derive(Deserialize)]
 pub struct ListDirectoryArgs {
@@ -15,6 +17,36 @@ pub struct ListDirectoryArgs {
 #[derive(Deserialize, Serialize)]
 pub struct ListDirectory;
 
+impl ToolInfo for ListDirectory {
+    const CATEGORY: ToolCategory = ToolCategory::Files;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "List files and directories in a given path";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory path to list (default: current directory)"
+                },
+                "show_hidden": {
+                    "type": "boolean",
+                    "description": "Show hidden files (default: false)"
+                }
+            }
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.path.clone().unwrap_or_else(|| ".".to_string())]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.path = Some(workspace.anchor(args.path.as_deref().unwrap_or(".")));
+    }
+}
+
 impl Tool for ListDirectory {
     const NAME: &'static str = "list_directory";
 
@@ -23,23 +55,7 @@ impl Tool for ListDirectory {
     type Output = Vec<String>;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "list_directory".to_string(),
-            description: "List files and directories in a given path".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "path": {
-                        "type": "string",
-                        "description": "Directory path to list (default: current directory)"
-                    },
-                    "show_hidden": {
-                        "type": "boolean",
-                        "description": "Show hidden files (default: false)"
-                    }
-                }
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
@@ -0,0 +1,448 @@
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+use super::ToolError;
+use super::registry::{CallReview, DynTool, ToolCategory, ToolSpec};
+use super::workspace::Workspace;
+use crate::config::{McpConfig, McpServerConfig};
+
+/// Protocol revision requested from servers
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Connecting and listing tools must finish within this
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest a single tool call may take
+const CALL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Tool names providers accept are at most this long
+const MAX_TOOL_NAME: usize = 64;
+
+/// Responses still awaited from a stdio server, by request id
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>>;
+
+enum Transport {
+    Stdio {
+        stdin: tokio::sync::Mutex<ChildStdin>,
+        pending: Pending,
+        /// Killed when the client is dropped
+        _child: Child,
+    },
+    Http {
+        client: reqwest::Client,
+        url: String,
+        headers: reqwest::header::HeaderMap,
+        /// Mcp-Session-Id assigned by the server on initialize
+        session: Mutex<Option<String>>,
+    },
+}
+
+/// Connection to one MCP server
+struct McpClient {
+    name: String,
+    transport: Transport,
+    next_id: AtomicU64,
+}
+
+impl McpClient {
+    /// Start or reach the server and complete the initialize handshake
+    async fn connect(name: &str, config: &McpServerConfig) -> Result<Self> {
+        let transport = match (&config.command, &config.url) {
+            (Some(command), _) => Self::spawn(command, config)?,
+            (None, Some(url)) => {
+                let mut headers = reqwest::header::HeaderMap::new();
+                for (header, value) in &config.headers {
+                    headers.insert(
+                        reqwest::header::HeaderName::from_bytes(header.as_bytes())
+                            .with_context(|| format!("Invalid header name '{}'", header))?,
+                        value.parse().with_context(|| format!("Invalid value for header '{}'", header))?,
+                    );
+                }
+                Transport::Http {
+                    client: reqwest::Client::builder().user_agent("Ada/1.0").build()?,
+                    url: url.clone(),
+                    headers,
+                    session: Mutex::new(None),
+                }
+            }
+            (None, None) => bail!("needs a command or a url"),
+        };
+
+        let client = Self {
+            name: name.to_string(),
+            transport,
+            next_id: AtomicU64::new(1),
+        };
+        let params = json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "ada", "version": env!("CARGO_PKG_VERSION") },
+        });
+        client.request("initialize", params, CONNECT_TIMEOUT).await?;
+        client.notify("notifications/initialized").await?;
+        Ok(client)
+    }
+
+    /// Run the server's command with a task routing its responses to their callers
+    fn spawn(command: &str, config: &McpServerConfig) -> Result<Transport> {
+        let mut child = Command::new(command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // Server logs would corrupt the TUI
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start '{}'", command))?;
+        let stdin = child.stdin.take().context("No stdin for the server")?;
+        let stdout = child.stdout.take().context("No stdout for the server")?;
+
+        let pending: Pending = Arc::default();
+        let routes = Arc::clone(&pending);
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                // Server requests and notifications (logging, progress) are ignored
+                if message.get("method").is_some() {
+                    continue;
+                }
+                let waiting = message["id"].as_u64().and_then(|id| routes.lock().unwrap_or_else(|e| e.into_inner()).remove(&id));
+                if let Some(sender) = waiting {
+                    let _ = sender.send(message);
+                }
+            }
+            // The server exited; dropping the senders fails every waiting call
+            routes.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        });
+
+        Ok(Transport::Stdio {
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending,
+            _child: child,
+        })
+    }
+
+    /// Send a request and return its result, or the server's error as an error
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let response = match tokio::time::timeout(timeout, self.exchange(id, &message)).await {
+            Ok(response) => response?,
+            Err(_) => {
+                if let Transport::Stdio { pending, .. } = &self.transport {
+                    pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+                }
+                bail!("{} timed out after {}s", method, timeout.as_secs());
+            }
+        };
+
+        if let Some(error) = response.get("error") {
+            bail!("{}", error["message"].as_str().unwrap_or("unknown error"));
+        }
+        Ok(response["result"].clone())
+    }
+
+    async fn notify(&self, method: &str) -> Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method });
+        match &self.transport {
+            Transport::Stdio { stdin, .. } => write_line(stdin, &message).await,
+            Transport::Http { .. } => self.post(&message).await.map(|_| ()),
+        }
+    }
+
+    /// Deliver a request and wait for the response with its id
+    async fn exchange(&self, id: u64, message: &Value) -> Result<Value> {
+        match &self.transport {
+            Transport::Stdio { stdin, pending, .. } => {
+                let (sender, receiver) = oneshot::channel();
+                pending.lock().unwrap_or_else(|e| e.into_inner()).insert(id, sender);
+                write_line(stdin, message).await?;
+                receiver.await.map_err(|_| anyhow!("the server exited"))
+            }
+            Transport::Http { .. } => {
+                let response = self.post(message).await?;
+                let is_stream = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| value.starts_with("text/event-stream"));
+                if !is_stream {
+                    return response.json().await.context("Invalid response");
+                }
+
+                // The response is one of the events; the rest are server notifications
+                let mut body = response.bytes_stream();
+                let mut buffer = String::new();
+                while let Some(chunk) = body.next().await {
+                    buffer.push_str(&String::from_utf8_lossy(&chunk?));
+                    while let Some(end) = buffer.find('\n') {
+                        let line: String = buffer.drain(..=end).collect();
+                        let Some(data) = line.trim_end().strip_prefix("data:") else {
+                            continue;
+                        };
+                        if let Ok(event) = serde_json::from_str::<Value>(data.trim_start()) {
+                            if event["id"].as_u64() == Some(id) {
+                                return Ok(event);
+                            }
+                        }
+                    }
+                }
+                bail!("the server closed the stream without responding")
+            }
+        }
+    }
+
+    /// POST one message to an HTTP server, keeping track of its session id
+    async fn post(&self, message: &Value) -> Result<reqwest::Response> {
+        let Transport::Http { client, url, headers, session } = &self.transport else {
+            bail!("not an HTTP server");
+        };
+
+        let mut request = client
+            .post(url)
+            .headers(headers.clone())
+            .header(reqwest::header::ACCEPT, "application/json, text/event-stream")
+            .header("MCP-Protocol-Version", PROTOCOL_VERSION)
+            .json(message);
+        let current = session.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(id) = current {
+            request = request.header("Mcp-Session-Id", id);
+        }
+
+        let response = request.send().await.with_context(|| format!("Failed to reach {}", url))?;
+        if !response.status().is_success() {
+            bail!("{} answered with status {}", url, response.status());
+        }
+        if let Some(id) = response.headers().get("Mcp-Session-Id").and_then(|value| value.to_str().ok()) {
+            *session.lock().unwrap_or_else(|e| e.into_inner()) = Some(id.to_string());
+        }
+        Ok(response)
+    }
+
+    /// Every tool the server offers, following pagination
+    async fn list_tools(&self) -> Result<Vec<Value>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params, CONNECT_TIMEOUT).await?;
+            tools.extend(result["tools"].as_array().cloned().unwrap_or_default());
+            cursor = result["nextCursor"].as_str().map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Call a tool, flattening its content into text. A result flagged isError
+    /// becomes a tool error.
+    async fn call_tool(&self, tool: &str, arguments: Value) -> std::result::Result<String, ToolError> {
+        let result = self
+            .request("tools/call", json!({ "name": tool, "arguments": arguments }), CALL_TIMEOUT)
+            .await
+            .map_err(|e| ToolError(format!("{}: {:#}", self.name, e)))?;
+
+        let parts: Vec<String> = result["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|item| match item["type"].as_str() {
+                Some("text") => item["text"].as_str().unwrap_or_default().to_string(),
+                Some("resource") => item["resource"]["text"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("[resource {}]", item["resource"]["uri"].as_str().unwrap_or_default())),
+                Some("resource_link") => format!("[resource {}]", item["uri"].as_str().unwrap_or_default()),
+                Some(other) => format!("[{} content omitted]", other),
+                None => String::new(),
+            })
+            .collect();
+        let mut text = parts.join("\n");
+        if text.is_empty() {
+            if let Some(structured) = result.get("structuredContent") {
+                text = serde_json::to_string_pretty(structured).unwrap_or_default();
+            }
+        }
+
+        if result["isError"].as_bool() == Some(true) {
+            return Err(ToolError(text));
+        }
+        Ok(text)
+    }
+}
+
+async fn write_line(stdin: &tokio::sync::Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(format!("{}\n", message).as_bytes()).await?;
+    stdin.flush().await.context("Failed to write to the server")
+}
+
+/// A tool of an MCP server, registered as <server>__<tool>
+#[derive(Clone)]
+pub struct McpTool {
+    client: Arc<McpClient>,
+    /// Name on the server
+    tool: String,
+    spec: ToolSpec,
+}
+
+#[async_trait]
+impl DynTool for McpTool {
+    fn spec(&self) -> ToolSpec {
+        self.spec.clone()
+    }
+
+    fn review(&self, _args: &Value) -> CallReview {
+        // Nothing is known about the arguments; only the server's read-only hint
+        CallReview {
+            mutates: self.spec.mutates,
+            ..CallReview::default()
+        }
+    }
+
+    async fn preview(&self, _args: &Value, _workspace: &Workspace) -> Option<String> {
+        None
+    }
+
+    async fn call_json(&self, args: Value, _workspace: &Workspace) -> std::result::Result<Value, ToolError> {
+        self.client.call_tool(&self.tool, args).await.map(Value::String)
+    }
+}
+
+/// The configured MCP servers that were reached at startup and their tools
+#[derive(Clone, Default)]
+pub struct McpServers {
+    tools: Vec<McpTool>,
+    /// Servers that couldn't be reached, with the reason
+    failed: Vec<(String, String)>,
+}
+
+impl McpServers {
+    /// Connect to every server in [mcp.servers] at once. A server that fails is
+    /// reported in `failures` and its tools are left out.
+    pub async fn connect(config: &McpConfig) -> Self {
+        let attempts = config.servers.iter().map(|(name, server)| async move {
+            let connected = tokio::time::timeout(CONNECT_TIMEOUT, async {
+                let client = Arc::new(McpClient::connect(name, server).await?);
+                let tools = client.list_tools().await?;
+                Ok::<_, anyhow::Error>((client, tools))
+            })
+            .await;
+            (name, connected)
+        });
+
+        let mut servers = Self::default();
+        for (name, connected) in futures::future::join_all(attempts).await {
+            match connected {
+                Ok(Ok((client, tools))) => {
+                    for tool in tools {
+                        let Some(tool_name) = tool["name"].as_str() else {
+                            continue;
+                        };
+                        let registered = unique_name(registered_name(name, tool_name), &servers.tools);
+                        servers.tools.push(McpTool {
+                            client: Arc::clone(&client),
+                            tool: tool_name.to_string(),
+                            spec: ToolSpec {
+                                name: registered,
+                                category: ToolCategory::Mcp,
+                                description: tool["description"]
+                                    .as_str()
+                                    .or(tool["title"].as_str())
+                                    .unwrap_or(tool_name)
+                                    .to_string(),
+                                // Assume the worst unless the server says otherwise
+                                mutates: tool["annotations"]["readOnlyHint"].as_bool() != Some(true),
+                                parameters: match tool.get("inputSchema") {
+                                    Some(schema) if schema.is_object() => schema.clone(),
+                                    _ => json!({ "type": "object", "properties": {} }),
+                                },
+                            },
+                        });
+                    }
+                }
+                Ok(Err(e)) => servers.failed.push((name.clone(), format!("{:#}", e))),
+                Err(_) => servers.failed.push((name.clone(), "timed out connecting".to_string())),
+            }
+        }
+        servers
+    }
+
+    /// Every configured server left unconnected; settings naming their tools still load
+    pub fn offline(config: &McpConfig) -> Self {
+        Self {
+            tools: Vec::new(),
+            failed: config.servers.keys().map(|name| (name.clone(), "not connected".to_string())).collect(),
+        }
+    }
+
+    pub fn tools(&self) -> &[McpTool] {
+        &self.tools
+    }
+
+    /// Registered names of the named server's tools
+    pub fn tool_names(&self, server: &str) -> Vec<String> {
+        let prefix = format!("{}__", server);
+        self.tools
+            .iter()
+            .map(|tool| tool.spec.name.clone())
+            .filter(|name| name.starts_with(&prefix))
+            .collect()
+    }
+
+    /// Servers that couldn't be reached and why
+    pub fn failures(&self) -> &[(String, String)] {
+        &self.failed
+    }
+
+    /// Whether the tool name belongs to a server that couldn't be reached
+    pub fn is_offline(&self, tool: &str) -> bool {
+        self.failed
+            .iter()
+            .any(|(server, _)| tool.strip_prefix(server.as_str()).is_some_and(|rest| rest.starts_with("__")))
+    }
+}
+
+/// <server>__<tool>, with characters providers reject replaced and cut to length
+fn registered_name(server: &str, tool: &str) -> String {
+    let tool: String = tool
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}__{}", server, tool).chars().take(MAX_TOOL_NAME).collect()
+}
+
+/// `name`, or `name` with a _2, _3, ... suffix if an earlier tool already has it:
+/// sanitizing and truncation can give two tools the same registered name
+fn unique_name(name: String, tools: &[McpTool]) -> String {
+    let taken = |candidate: &str| tools.iter().any(|tool| tool.spec.name == candidate);
+    if !taken(&name) {
+        return name;
+    }
+    (2..)
+        .map(|n| {
+            let suffix = format!("_{}", n);
+            let base: String = name.chars().take(MAX_TOOL_NAME - suffix.len()).collect();
+            base + &suffix
+        })
+        .find(|candidate| !taken(candidate))
+        .expect("a free suffix exists")
+}
@@ -12,6 +12,10 @@ pub mod list_directory;
 pub mod write_files;
 pub mod file_ops;
 pub mod tree;
+pub mod mcp;
+pub mod policy;
+pub mod registry;
+pub mod workspace;
 
 // Re-export tools for easy access
 pub use read_file::ReadFile;
@@ -26,6 +30,8 @@ pub use list_directory::ListDirectory;
 pub use write_files::WriteFiles;
 pub use file_ops::FileOps;
 pub use tree::Tree;
+pub use mcp::McpServers;
+pub use registry::{ToolCategory, ToolRegistry, ToolSpec};
 
 // Common error type for all tools
 #[derive(Debug, thiserror::Error)]
@@ -0,0 +1,236 @@
+use serde_json::Value;
+
+use super::ToolError;
+use crate::config::ToolsConfig;
+
+/// Programs that run another command given as their arguments: the wrapper, its
+/// options that take a value, and how many arguments come before the command
+const WRAPPERS: &[(&str, &[&str], usize)] = &[
+    ("sudo", &["-u", "-g", "-p", "-C", "-D", "-h", "-r", "-t", "-U", "--user", "--group", "--chdir"], 0),
+    ("env", &["-u", "-C", "--unset", "--chdir"], 0),
+    ("nice", &["-n", "--adjustment"], 0),
+    ("timeout", &["-s", "-k", "--signal", "--kill-after"], 1),
+    ("stdbuf", &["-i", "-o", "-e", "--input", "--output", "--error"], 0),
+    ("xargs", &["-I", "-n", "-P", "-L", "-d", "-E", "-s", "-a", "--max-args", "--max-procs", "--delimiter"], 0),
+    ("nohup", &[], 0),
+    ("command", &[], 0),
+    ("exec", &["-a"], 0),
+    ("time", &[], 0),
+];
+
+/// Whether a host is one of `allowed` or a subdomain of one
+pub fn domain_allowed(host: &str, allowed: &[String]) -> bool {
+    let host = host.to_lowercase();
+    allowed.iter().any(|domain| {
+        let domain = domain.trim_start_matches("*.").to_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    })
+}
+
+/// Restrictions from [tools], checked before a tool runs
+#[derive(Debug, Clone, Default)]
+pub struct ToolPolicy {
+    config: ToolsConfig,
+}
+
+impl ToolPolicy {
+    pub fn new(config: &ToolsConfig) -> Self {
+        Self { config: config.clone() }
+    }
+
+    /// Whether the tool may be offered to agents at all
+    pub fn is_enabled(&self, name: &str) -> bool {
+        let allowed = self
+            .config
+            .allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|t| t == name));
+        allowed && !self.config.deny.iter().any(|t| t == name)
+    }
+
+    /// Refuse a call that the per-tool settings don't permit
+    pub fn check(&self, name: &str, args: &Value) -> Result<(), ToolError> {
+        if !self.is_enabled(name) {
+            return Err(ToolError(format!("The {} tool is disabled by configuration", name)));
+        }
+
+        let arg = |key: &str| args.get(key).and_then(Value::as_str).unwrap_or_default();
+        match name {
+            "webfetch" => self.check_url(arg("url")),
+            "execute" => self.check_command(arg("command")),
+            "file_ops" => self.check_file_operation(arg("operation")),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_url(&self, url: &str) -> Result<(), ToolError> {
+        let allowed = &self.config.webfetch.allowed_domains;
+        if allowed.is_empty() {
+            return Ok(());
+        }
+
+        let parsed = reqwest::Url::parse(url).map_err(|e| ToolError(format!("Invalid URL {}: {}", url, e)))?;
+        let host = parsed.host_str().unwrap_or_default().to_lowercase();
+
+        if domain_allowed(&host, allowed) {
+            Ok(())
+        } else {
+            Err(ToolError(format!(
+                "Fetching from {} is not allowed (tools.webfetch.allowed_domains: {})",
+                host,
+                allowed.join(", ")
+            )))
+        }
+    }
+
+    fn check_command(&self, command: &str) -> Result<(), ToolError> {
+        let denied = &self.config.execute.denied_commands;
+
+        // Check every command in a pipeline, chain, subshell or command substitution,
+        // not just the first
+        for segment in command.split(['\n', ';', '|', '&', '(', ')', '`']) {
+            let mut words: Vec<&str> = segment.split_whitespace().collect();
+            // Check the wrapper and the program it runs: sudo, then rm in sudo rm
+            while let Some(first) = words.first() {
+                if let Some(entry) = denied_entry(denied, &words) {
+                    return Err(ToolError(format!(
+                        "Command refused: '{}' is in tools.execute.denied_commands",
+                        entry
+                    )));
+                }
+
+                let program = first.rsplit('/').next().unwrap_or(first);
+                if let Some((_, valued, positional)) = WRAPPERS.iter().find(|(name, ..)| *name == program) {
+                    words.remove(0);
+                    // Options, with the values of those that take one: sudo -u root rm
+                    while let Some(word) = words.first() {
+                        if valued.contains(word) {
+                            words.drain(..words.len().min(2));
+                        } else if word.starts_with('-') {
+                            words.remove(0);
+                        } else {
+                            break;
+                        }
+                    }
+                    // Arguments before the command: timeout 5 rm
+                    words.drain(..words.len().min(*positional));
+                } else if first.split_once('=').is_some_and(|(name, _)| !name.is_empty()) {
+                    // Variable assignment before the program: env X=1 rm
+                    words.remove(0);
+                } else {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_file_operation(&self, operation: &str) -> Result<(), ToolError> {
+        if self.config.file_ops.denied_operations.iter().any(|op| op == operation) {
+            Err(ToolError(format!(
+                "The {} operation is disabled by tools.file_ops.denied_operations",
+                operation
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The denied_commands entry matching a command's words, if any
+fn denied_entry<'a>(denied: &'a [String], words: &[&str]) -> Option<&'a String> {
+    let program = words.first()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+    let normalized = words.join(" ");
+    denied.iter().find(|entry| {
+        let entry = entry.trim();
+        if entry.contains(' ') {
+            normalized == entry || normalized.starts_with(&format!("{} ", entry))
+        } else {
+            program == entry
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(denied: &[&str]) -> ToolPolicy {
+        let mut config = ToolsConfig::default();
+        config.execute.denied_commands = denied.iter().map(|d| d.to_string()).collect();
+        ToolPolicy::new(&config)
+    }
+
+    fn refused(denied: &[&str], command: &str) -> bool {
+        policy(denied).check_command(command).is_err()
+    }
+
+    #[test]
+    fn allows_other_programs() {
+        assert!(!refused(&["rm"], "ls -la && cargo build"));
+        assert!(!refused(&["rm"], "sudo -u root ls"));
+    }
+
+    #[test]
+    fn checks_chains_subshells_and_substitutions() {
+        assert!(refused(&["rm"], "ls; rm -rf x"));
+        assert!(refused(&["rm"], "echo $(rm -rf x)"));
+        assert!(refused(&["rm"], "echo `rm -rf x`"));
+        assert!(refused(&["rm"], "(rm x)"));
+    }
+
+    #[test]
+    fn skips_sudo_options() {
+        assert!(refused(&["rm"], "sudo rm -rf x"));
+        assert!(refused(&["rm"], "sudo -u root rm -rf x"));
+        assert!(refused(&["rm"], "sudo -g wheel -E rm x"));
+    }
+
+    #[test]
+    fn skips_env_options_and_assignments() {
+        assert!(refused(&["rm"], "env rm x"));
+        assert!(refused(&["rm"], "env -u HOME rm x"));
+        assert!(refused(&["rm"], "env FOO=1 rm x"));
+        assert!(refused(&["rm"], "FOO=1 rm x"));
+    }
+
+    #[test]
+    fn skips_nice_adjustment() {
+        assert!(refused(&["rm"], "nice -n 10 rm x"));
+    }
+
+    #[test]
+    fn skips_timeout_duration() {
+        assert!(refused(&["rm"], "timeout 5 rm x"));
+        assert!(refused(&["rm"], "timeout -s KILL 5s rm x"));
+    }
+
+    #[test]
+    fn skips_stdbuf_modes() {
+        assert!(refused(&["rm"], "stdbuf -oL rm x"));
+        assert!(refused(&["rm"], "stdbuf -o L rm x"));
+    }
+
+    #[test]
+    fn skips_xargs_options() {
+        assert!(refused(&["rm"], "find . | xargs rm"));
+        assert!(refused(&["rm"], "find . | xargs -I {} rm {}"));
+        assert!(refused(&["rm"], "find . | xargs -n 1 -P 4 rm"));
+    }
+
+    #[test]
+    fn skips_nohup_command_exec_and_time() {
+        assert!(refused(&["rm"], "nohup rm x"));
+        assert!(refused(&["rm"], "command rm x"));
+        assert!(refused(&["rm"], "exec -a name rm x"));
+        assert!(refused(&["rm"], "time rm x"));
+    }
+
+    #[test]
+    fn wrappers_themselves_can_be_denied() {
+        assert!(refused(&["sudo"], "sudo ls"));
+        assert!(refused(&["git push"], "sudo -u me git push origin"));
+    }
+}
@@ -1,10 +1,12 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use tokio::fs;
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
 
 #[derive(Deserialize)]
 pub struct ReadFileArgs {
@@ -14,6 +16,33 @@ pub struct ReadFileArgs {
 #[derive(Deserialize, Serialize)]
 pub struct ReadFile;
 
+impl ToolInfo for ReadFile {
+    const CATEGORY: ToolCategory = ToolCategory::Files;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "Read the contents of a file from the filesystem";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The path to the file to read"
+                }
+            },
+            "required": ["file_path"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.file_path.clone()]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.file_path = workspace.anchor(&args.file_path);
+    }
+}
+
 impl Tool for ReadFile {
     const NAME: &'static str = "read_file";
 
@@ -22,20 +51,7 @@ impl Tool for ReadFile {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "read_file".to_string(),
-            description: "Read the contents of a file from the filesystem".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "file_path": {
-                        "type": "string",
-                        "description": "The path to the file to read"
-                    }
-                },
-                "required": ["file_path"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
@@ -0,0 +1,389 @@
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use rig::completion::ToolDefinition;
+use rig::tool::Tool;
+use serde_json::Value;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::mcp::McpServers;
+use super::policy::ToolPolicy;
+use super::workspace::Workspace;
+use super::*;
+use crate::approval::Approvals;
+use crate::config::{Config, OutsideAccess};
+
+/// Grouping used for /help and tool listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ToolCategory {
+    Search,
+    Files,
+    Git,
+    Shell,
+    Web,
+    /// Tools of external MCP servers
+    Mcp,
+}
+
+impl std::fmt::Display for ToolCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ToolCategory::Search => "Search",
+            ToolCategory::Files => "Files",
+            ToolCategory::Git => "Git",
+            ToolCategory::Shell => "Shell",
+            ToolCategory::Web => "Web",
+            ToolCategory::Mcp => "MCP",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What a tool says about itself; the rig definition is built from this
+pub trait ToolInfo: Tool {
+    const CATEGORY: ToolCategory;
+    /// Whether the tool changes files, the repository or anything outside Ada
+    const MUTATES: bool;
+    const DESCRIPTION: &'static str;
+
+    /// JSON schema of the tool's arguments
+    fn parameters() -> Value;
+
+    /// Whether this particular call changes anything, so read-only uses of a
+    /// mutating tool (git status) don't need approval
+    fn call_mutates(&self, _args: &Self::Args) -> bool {
+        Self::MUTATES
+    }
+
+    /// Filesystem paths the call touches, checked against the workspace root
+    fn paths(&self, _args: &Self::Args) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Make the call's relative paths relative to the workspace root, the same paths
+    /// `paths` reported, before previewing or running it
+    fn anchor(&self, _args: &mut Self::Args, _workspace: &Workspace) {}
+
+    /// What the call would change, shown when asking for approval. Only built when
+    /// the user is actually asked.
+    fn preview(&self, _args: &Self::Args) -> impl Future<Output = Option<String>> + Send {
+        async { None }
+    }
+}
+
+/// Rig definition for a tool, from its `ToolInfo`
+pub fn definition<T: ToolInfo>() -> ToolDefinition {
+    ToolDefinition {
+        name: T::NAME.to_string(),
+        description: T::DESCRIPTION.to_string(),
+        parameters: T::parameters(),
+    }
+}
+
+/// Registry entry describing one tool
+#[derive(Debug, Clone)]
+pub struct ToolSpec {
+    pub name: String,
+    pub category: ToolCategory,
+    pub description: String,
+    pub mutates: bool,
+    pub parameters: Value,
+}
+
+/// What a call is about to do, worked out before it runs
+#[derive(Debug, Default)]
+pub struct CallReview {
+    pub mutates: bool,
+    pub paths: Vec<String>,
+}
+
+/// Object-safe view of a tool, so tools of different types can live in one registry
+#[async_trait]
+pub trait DynTool: Send + Sync {
+    fn spec(&self) -> ToolSpec;
+
+    /// Whether the call mutates anything and the paths it touches
+    fn review(&self, args: &Value) -> CallReview;
+
+    /// What the call would change, for the approval prompt
+    async fn preview(&self, args: &Value, workspace: &Workspace) -> Option<String>;
+
+    /// Run the tool with JSON arguments, returning its JSON-encoded output
+    async fn call_json(&self, args: Value, workspace: &Workspace) -> std::result::Result<Value, ToolError>;
+}
+
+#[async_trait]
+impl<T> DynTool for T
+where
+    T: ToolInfo + Tool<Error = ToolError> + 'static,
+{
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: T::NAME.to_string(),
+            category: T::CATEGORY,
+            description: T::DESCRIPTION.to_string(),
+            mutates: T::MUTATES,
+            parameters: T::parameters(),
+        }
+    }
+
+    fn review(&self, args: &Value) -> CallReview {
+        match serde_json::from_value::<T::Args>(args.clone()) {
+            Ok(args) => CallReview {
+                mutates: self.call_mutates(&args),
+                paths: self.paths(&args),
+            },
+            // The call itself will report the bad arguments
+            Err(_) => CallReview {
+                mutates: T::MUTATES,
+                ..CallReview::default()
+            },
+        }
+    }
+
+    async fn preview(&self, args: &Value, workspace: &Workspace) -> Option<String> {
+        let mut args = serde_json::from_value::<T::Args>(args.clone()).ok()?;
+        self.anchor(&mut args, workspace);
+        ToolInfo::preview(self, &args).await
+    }
+
+    async fn call_json(&self, args: Value, workspace: &Workspace) -> std::result::Result<Value, ToolError> {
+        let mut args: T::Args = serde_json::from_value(args)
+            .map_err(|e| ToolError(format!("Invalid arguments for {}: {}", T::NAME, e)))?;
+        self.anchor(&mut args, workspace);
+        let output = self.call(args).await?;
+        serde_json::to_value(output).map_err(|e| ToolError(format!("Failed to encode {} output: {}", T::NAME, e)))
+    }
+}
+
+/// A registered tool as handed to an agent; every call goes through the registry entry
+#[derive(Clone)]
+pub struct RegisteredTool {
+    spec: ToolSpec,
+    tool: Arc<dyn DynTool>,
+    policy: Arc<ToolPolicy>,
+    approvals: Arc<Approvals>,
+    workspace: Arc<Workspace>,
+    /// Calls that returned an error, shared by every tool in the registry
+    failures: Arc<AtomicUsize>,
+}
+
+impl Tool for RegisteredTool {
+    // Unused: the registered name comes from the spec
+    const NAME: &'static str = "registered_tool";
+
+    type Error = ToolError;
+    type Args = Value;
+    type Output = Value;
+
+    fn name(&self) -> String {
+        self.spec.name.clone()
+    }
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: self.spec.name.clone(),
+            description: self.spec.description.clone(),
+            parameters: self.spec.parameters.clone(),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> std::result::Result<Self::Output, Self::Error> {
+        let result = self.checked_call(args).await;
+        if result.is_err() {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+impl RegisteredTool {
+    /// Policy, workspace and approval checks, then the tool itself
+    async fn checked_call(&self, args: Value) -> std::result::Result<Value, ToolError> {
+        self.policy.check(&self.spec.name, &args)?;
+
+        let review = self.tool.review(&args);
+        let outside = self.workspace.outside_paths(&review.paths);
+        if !outside.is_empty() && self.workspace.outside_access() == OutsideAccess::Deny {
+            return Err(ToolError(format!(
+                "{} is outside the workspace {}",
+                outside[0].display(),
+                self.workspace.root().display()
+            )));
+        }
+
+        if self.approvals.needs_approval(&self.spec.name, review.mutates, &outside) {
+            // The preview may read files, so it is only built when someone is asked.
+            // A denial is returned as the tool's output so the model sees the reason.
+            let preview = self.tool.preview(&args, &self.workspace).await;
+            let approval = self
+                .approvals
+                .authorize(&self.spec, &args, preview, review.mutates, &outside)
+                .await;
+            if let Err(denial) = approval {
+                return Ok(Value::String(denial));
+            }
+        }
+
+        self.tool.call_json(args, &self.workspace).await
+    }
+}
+
+/// Every tool Ada knows about. Adding a tool means registering it in `new`.
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: Vec<RegisteredTool>,
+    policy: Arc<ToolPolicy>,
+    approvals: Arc<Approvals>,
+    workspace: Arc<Workspace>,
+    failures: Arc<AtomicUsize>,
+    /// Connected MCP servers, whose tools follow the built-in ones
+    mcp: McpServers,
+}
+
+impl ToolRegistry {
+    /// Built-in tools and those of the connected MCP servers, restricted by [tools],
+    /// confined to the workspace and gated by `approvals`, which outlives the
+    /// registry when agents are rebuilt
+    pub fn new(config: &Config, approvals: Arc<Approvals>, mcp: &McpServers) -> Result<Self> {
+        let workspace = Arc::new(Workspace::new(&config.workspace)?);
+        let mut registry = Self {
+            tools: Vec::new(),
+            policy: Arc::new(ToolPolicy::new(&config.tools)),
+            approvals,
+            workspace: Arc::clone(&workspace),
+            failures: Arc::new(AtomicUsize::new(0)),
+            mcp: mcp.clone(),
+        };
+        registry.register(ReadFile);
+        registry.register(SearchDirectory::new(&workspace));
+        registry.register(Edit);
+        registry.register(Grep::new(&workspace));
+        registry.register(Glob::new(&workspace));
+        registry.register(Git::new(&workspace));
+        registry.register(WebFetch::new(&config.tools.webfetch));
+        registry.register(Execute);
+        registry.register(ListDirectory);
+        registry.register(WriteFiles);
+        registry.register(FileOps);
+        registry.register(Tree::new(&workspace));
+        for tool in mcp.tools() {
+            registry.register(tool.clone());
+        }
+
+        let mut listed = config.tools.allow.iter().flatten().chain(&config.tools.deny);
+        if let Some(unknown) = listed.find(|name| !registry.contains(name)) {
+            bail!("tools: unknown tool '{}' (available: {})", unknown, registry.names());
+        }
+
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, tool: impl DynTool + 'static) {
+        let tool: Arc<dyn DynTool> = Arc::new(tool);
+        self.tools.push(RegisteredTool {
+            spec: tool.spec(),
+            tool,
+            policy: Arc::clone(&self.policy),
+            approvals: Arc::clone(&self.approvals),
+            workspace: Arc::clone(&self.workspace),
+            failures: Arc::clone(&self.failures),
+        });
+    }
+
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// Approval gate shared by every mutating tool
+    pub fn approvals(&self) -> &Approvals {
+        &self.approvals
+    }
+
+    /// Shared handle to the approval gate, for a rebuilt registry to keep using
+    pub fn approvals_handle(&self) -> Arc<Approvals> {
+        Arc::clone(&self.approvals)
+    }
+
+    /// An enabled tool; disabled tools are never handed to agents
+    pub fn get(&self, name: &str) -> Option<&RegisteredTool> {
+        self.tools
+            .iter()
+            .find(|t| t.spec.name == name)
+            .filter(|t| self.policy.is_enabled(&t.spec.name))
+    }
+
+    /// Whether the tool exists, enabled or not. Tools of an MCP server that couldn't
+    /// be reached count, so settings naming them still load.
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.iter().any(|t| t.spec.name == name) || self.mcp.is_offline(name)
+    }
+
+    /// MCP servers whose tools are registered
+    pub fn mcp(&self) -> &McpServers {
+        &self.mcp
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.policy.is_enabled(name)
+    }
+
+    /// Run an enabled tool on the user's behalf (direct commands). [tools] restrictions
+    /// apply; approval doesn't, since the user typed the command themselves.
+    pub async fn call(&self, name: &str, args: Value) -> std::result::Result<Value, ToolError> {
+        let Some(tool) = self.get(name) else {
+            return Err(ToolError(format!("The {} tool is not available", name)));
+        };
+        self.policy.check(name, &args)?;
+        tool.tool.call_json(args, &self.workspace).await
+    }
+
+    /// Agent tool calls that returned an error so far, plus failed direct commands
+    pub fn failed_calls(&self) -> usize {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Count a call made through `call` that failed
+    pub fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ToolSpec> {
+        self.tools.iter().map(|t| &t.spec)
+    }
+
+    /// Comma-separated names of all tools, for error messages
+    pub fn names(&self) -> String {
+        self.iter().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Tools grouped by category, in category order
+    pub fn by_category(&self) -> Vec<(ToolCategory, Vec<&ToolSpec>)> {
+        let mut groups: Vec<(ToolCategory, Vec<&ToolSpec>)> = Vec::new();
+        for spec in self.iter() {
+            match groups.iter_mut().find(|(category, _)| *category == spec.category) {
+                Some((_, specs)) => specs.push(spec),
+                None => groups.push((spec.category, vec![spec])),
+            }
+        }
+        groups.sort_by_key(|(category, _)| *category);
+        groups
+    }
+
+    /// Listing for /help and `ada tools list`; * marks tools that change files or run commands
+    pub fn describe(&self) -> String {
+        let mut output = String::from("Tools (* changes files or runs commands):\n");
+        for (category, specs) in self.by_category() {
+            output.push_str(&format!("  {}:\n", category));
+            for spec in specs {
+                output.push_str(&format!("    - {}{} - {}{}\n",
+                    spec.name,
+                    if spec.mutates { "*" } else { "" },
+                    spec.description,
+                    if self.is_enabled(&spec.name) { "" } else { " (disabled)" }
+                ));
+            }
+        }
+        output
+    }
+}
@@ -2,9 +2,12 @@ use ignore::WalkBuilder;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::{self, Workspace};
 
 #[derive(Deserialize)]
 pub struct SearchDirectoryArgs {
@@ -13,7 +16,49 @@ pub struct SearchDirectoryArgs {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct SearchDirectory;
+pub struct SearchDirectory {
+    /// Workspace root; symlinks found while walking must stay inside it
+    root: PathBuf,
+}
+
+impl SearchDirectory {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            root: workspace.root().to_path_buf(),
+        }
+    }
+}
+
+impl ToolInfo for SearchDirectory {
+    const CATEGORY: ToolCategory = ToolCategory::Search;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "Search for files in a directory, optionally filtering by pattern";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "directory": {
+                    "type": "string",
+                    "description": "The directory to search in"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Optional pattern to filter files (e.g., '.rs', 'cargo')"
+                }
+            },
+            "required": ["directory"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.directory.clone()]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.directory = workspace.anchor(&args.directory);
+    }
+}
 
 impl Tool for SearchDirectory {
     const NAME: &'static str = "search_directory";
@@ -23,30 +68,13 @@ impl Tool for SearchDirectory {
     type Output = Vec<String>;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "search_directory".to_string(),
-            description: "Search for files in a directory, optionally filtering by pattern"
-                .to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "directory": {
-                        "type": "string",
-                        "description": "The directory to search in"
-                    },
-                    "pattern": {
-                        "type": "string",
-                        "description": "Optional pattern to filter files (e.g., '.rs', 'cargo')"
-                    }
-                },
-                "required": ["directory"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         let mut results = Vec::new();
 
+        let start = Path::new(&args.directory).canonicalize().unwrap_or_else(|_| self.root.clone());
         for result in WalkBuilder::new(&args.directory)
             .git_ignore(true)
             .git_global(true)
@@ -55,6 +83,9 @@ impl Tool for SearchDirectory {
             .build()
         {
             let entry = result.map_err(|e| ToolError(format!("Walk error: {}", e)))?;
+            if workspace::escapes(&self.root, &start, &entry) {
+                continue;
+            }
             let path = entry.path();
 
             // Skip directories
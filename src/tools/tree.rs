@@ -2,10 +2,12 @@ use ignore::WalkBuilder;
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::path::Path;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
 
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::{self, Workspace};
 
 #[derive(Deserialize)]
 pub struct TreeArgs {
@@ -14,7 +16,48 @@ pub struct TreeArgs {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct Tree;
+pub struct Tree {
+    /// Workspace root; symlinks found while walking must stay inside it
+    root: PathBuf,
+}
+
+impl Tree {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            root: workspace.root().to_path_buf(),
+        }
+    }
+}
+
+impl ToolInfo for Tree {
+    const CATEGORY: ToolCategory = ToolCategory::Files;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "Display directory structure as a tree";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory path to display (default: current directory)"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "description": "Maximum depth to traverse (default: 3)"
+                }
+            }
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        vec![args.path.clone().unwrap_or_else(|| ".".to_string())]
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        args.path = Some(workspace.anchor(args.path.as_deref().unwrap_or(".")));
+    }
+}
 
 impl Tool for Tree {
     const NAME: &'static str = "tree";
@@ -24,23 +67,7 @@ impl Tool for Tree {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "tree".to_string(),
-            description: "Display directory structure as a tree".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "path": {
-                        "type": "string",
-                        "description": "Directory path to display (default: current directory)"
-                    },
-                    "max_depth": {
-                        "type": "integer",
-                        "description": "Maximum depth to traverse (default: 3)"
-                    }
-                }
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
@@ -53,6 +80,7 @@ impl Tool for Tree {
 
         let mut entries: Vec<(String, usize, bool)> = Vec::new();
 
+        let start = Path::new(root_path).canonicalize().unwrap_or_else(|_| self.root.clone());
         for entry_result in WalkBuilder::new(root_path)
             .git_ignore(true)
             .git_global(true)
@@ -62,6 +90,9 @@ impl Tool for Tree {
             .build()
         {
             let entry = entry_result.map_err(|e| ToolError(format!("Walk error: {}", e)))?;
+            if workspace::escapes(&self.root, &start, &entry) {
+                continue;
+            }
             let path = entry.path();
 
             if path == root {
@@ -1,9 +1,12 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 
+use super::policy::domain_allowed;
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use crate::config::WebFetchConfig;
 
 #[derive(Deserialize)]
 pub struct WebFetchArgs {
@@ -11,7 +14,37 @@ pub struct WebFetchArgs {
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct WebFetch;
+pub struct WebFetch {
+    /// tools.webfetch.allowed_domains, re-checked on every redirect
+    allowed_domains: Vec<String>,
+}
+
+impl WebFetch {
+    pub fn new(config: &WebFetchConfig) -> Self {
+        Self {
+            allowed_domains: config.allowed_domains.clone(),
+        }
+    }
+}
+
+impl ToolInfo for WebFetch {
+    const CATEGORY: ToolCategory = ToolCategory::Web;
+    const MUTATES: bool = false;
+    const DESCRIPTION: &'static str = "Fetch content from a URL (useful for reading documentation, APIs, etc.)";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The URL to fetch"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+}
 
 impl Tool for WebFetch {
     const NAME: &'static str = "webfetch";
@@ -21,27 +54,27 @@ impl Tool for WebFetch {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "webfetch".to_string(),
-            description: "Fetch content from a URL (useful for reading documentation, APIs, etc.)"
-                .to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "url": {
-                        "type": "string",
-                        "description": "The URL to fetch"
-                    }
-                },
-                "required": ["url"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        // The policy only checked the first URL, so redirects must stay on allowed hosts too
+        let allowed = self.allowed_domains.clone();
+        let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+            let host = attempt.url().host_str().unwrap_or_default().to_string();
+            if attempt.previous().len() >= 10 {
+                attempt.error("too many redirects")
+            } else if allowed.is_empty() || domain_allowed(&host, &allowed) {
+                attempt.follow()
+            } else {
+                attempt.error(format!("redirect to {} is not allowed (tools.webfetch.allowed_domains)", host))
+            }
+        });
+
         let client = reqwest::Client::builder()
             .user_agent("Ada/1.0")
             .timeout(std::time::Duration::from_secs(30))
+            .redirect(redirects)
             .build()
             .map_err(|e| ToolError(format!("Failed to create HTTP client: {}", e)))?;
 
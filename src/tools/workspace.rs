@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::{OutsideAccess, WorkspaceConfig};
+
+/// Directory tree the filesystem tools are confined to
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// Canonical root, so symlinked paths compare correctly
+    root: PathBuf,
+    outside: OutsideAccess,
+    /// Whether Ada was started in the root, so relative paths already point into it
+    started_in_root: bool,
+}
+
+impl Workspace {
+    /// Root from [workspace].root, or the directory Ada was started in
+    pub fn new(config: &WorkspaceConfig) -> Result<Self> {
+        let cwd = std::env::current_dir().context("Failed to read the current directory")?;
+        let root = config.root.as_ref().unwrap_or(&cwd);
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Workspace root {} does not exist", root.display()))?;
+        let started_in_root = cwd.canonicalize().is_ok_and(|cwd| cwd == root);
+
+        Ok(Self {
+            root,
+            outside: config.outside,
+            started_in_root,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn outside_access(&self) -> OutsideAccess {
+        self.outside
+    }
+
+    /// A path argument as the tools use it: relative paths are taken from the root,
+    /// not from the directory Ada was started in
+    pub fn anchor(&self, path: &str) -> String {
+        if self.started_in_root || Path::new(path).is_absolute() {
+            return path.to_string();
+        }
+        normalize(&self.root.join(path)).display().to_string()
+    }
+
+    /// The paths that resolve outside the root, after following symlinks
+    pub fn outside_paths(&self, paths: &[String]) -> Vec<PathBuf> {
+        paths
+            .iter()
+            .map(|path| resolve(&self.root.join(path)))
+            .filter(|resolved| !resolved.starts_with(&self.root))
+            .collect()
+    }
+}
+
+/// Whether a walked entry is a symlink leading out of both the workspace root and
+/// the directory the walk started in (`start`, canonical). Only the starting path is
+/// checked before a walking tool runs, so every link it meets is checked here.
+pub fn escapes(root: &Path, start: &Path, entry: &ignore::DirEntry) -> bool {
+    if !entry.path_is_symlink() {
+        return false;
+    }
+    match entry.path().canonicalize() {
+        Ok(target) => !target.starts_with(root) && !target.starts_with(start),
+        // A dangling link can't be read anyway
+        Err(_) => true,
+    }
+}
+
+/// Canonical form of an absolute path that may not exist yet: the longest existing
+/// ancestor is canonicalized (resolving symlinks) and the rest is appended
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_owned());
+                existing = parent;
+            }
+            // ".." or a root that doesn't exist: let the lexical check below decide
+            _ => break,
+        }
+    }
+
+    let mut resolved = existing.canonicalize().unwrap_or_else(|_| normalize(existing));
+    for name in rest.into_iter().rev() {
+        resolved.push(name);
+    }
+    normalize(&resolved)
+}
+
+/// Remove "." and ".." components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
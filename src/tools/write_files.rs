@@ -1,12 +1,14 @@
 use rig::completion::ToolDefinition;
 use rig::tool::Tool;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use std::path::Path;
 use tokio::fs;
 
 use super::formatter::{create_diff, ToolOutput};
+use super::registry::{definition, ToolCategory, ToolInfo};
 use super::ToolError;
+use super::workspace::Workspace;
 
 #[derive(Deserialize)]
 pub struct FileToWrite {
@@ -22,6 +24,59 @@ pub struct WriteFilesArgs {
 #[derive(Deserialize, Serialize)]
 pub struct WriteFiles;
 
+impl ToolInfo for WriteFiles {
+    const CATEGORY: ToolCategory = ToolCategory::Files;
+    const MUTATES: bool = true;
+    const DESCRIPTION: &'static str = "Write content to multiple files at once";
+
+    fn parameters() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "description": "Array of files to write",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "File path"
+                            },
+                            "content": {
+                                "type": "string",
+                                "description": "File content"
+                            }
+                        },
+                        "required": ["path", "content"]
+                    }
+                }
+            },
+            "required": ["files"]
+        })
+    }
+
+    fn paths(&self, args: &Self::Args) -> Vec<String> {
+        args.files.iter().map(|file| file.path.clone()).collect()
+    }
+
+    fn anchor(&self, args: &mut Self::Args, workspace: &Workspace) {
+        for file in &mut args.files {
+            file.path = workspace.anchor(&file.path);
+        }
+    }
+
+    async fn preview(&self, args: &Self::Args) -> Option<String> {
+        let mut previews = Vec::new();
+        for file in &args.files {
+            let old_content = fs::read_to_string(&file.path).await.unwrap_or_default();
+            let diff = create_diff(&file.path, &old_content, &file.content, 2);
+            previews.push(ToolOutput::new("WriteFile", &file.path).with_diff(diff).format());
+        }
+        Some(previews.join("\n"))
+    }
+}
+
 impl Tool for WriteFiles {
     const NAME: &'static str = "write_files";
 
@@ -30,34 +85,7 @@ impl Tool for WriteFiles {
     type Output = String;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
-        ToolDefinition {
-            name: "write_files".to_string(),
-            description: "Write content to multiple files at once".to_string(),
-            parameters: json!({
-                "type": "object",
-                "properties": {
-                    "files": {
-                        "type": "array",
-                        "description": "Array of files to write",
-                        "items": {
-                            "type": "object",
-                            "properties": {
-                                "path": {
-                                    "type": "string",
-                                    "description": "File path"
-                                },
-                                "content": {
-                                    "type": "string",
-                                    "description": "File content"
-                                }
-                            },
-                            "required": ["path", "content"]
-                        }
-                    }
-                },
-                "required": ["files"]
-            }),
-        }
+        definition::<Self>()
     }
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
@@ -9,10 +9,16 @@ use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame, Terminal,
 };
+use std::collections::VecDeque;
 use std::io;
+use std::time::Duration;
+
+use crate::approval::ApprovalDecision;
+use crate::events::{self, AgentEvent};
+use crate::session::SessionEntry;
 
 #[derive(Debug, Clone)]
 pub enum MessageRole {
@@ -27,11 +33,36 @@ pub struct Message {
     pub content: String,
 }
 
+/// A mutating tool call shown in the approval modal
+#[derive(Debug, Clone)]
+pub struct PendingApproval {
+    pub id: u64,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub preview: Option<String>,
+    /// Set while the user is typing a reason for denying the call
+    pub reason: Option<String>,
+}
+
 pub struct App {
     pub messages: Vec<Message>,
     pub input: String,
     pub should_quit: bool,
     pub is_processing: bool,
+    /// Set when Esc is pressed during processing; the main loop cancels the run
+    pub interrupt_requested: bool,
+    /// Messages submitted while a request was running, sent in order once it finishes
+    pub queued: VecDeque<String>,
+    /// Lines scrolled up from the bottom of the transcript
+    pub scroll: u16,
+    /// Show the classified intent in the header of streamed responses
+    pub show_intent: bool,
+    /// Tool call waiting for the user's decision
+    pub approval: Option<PendingApproval>,
+    /// Decision made in the approval modal, delivered by the main loop
+    pub approval_response: Option<(u64, ApprovalDecision)>,
+    /// Index of the assistant message currently being streamed
+    streaming: Option<usize>,
 }
 
 impl App {
@@ -50,6 +81,13 @@ impl App {
             input: String::new(),
             should_quit: false,
             is_processing: false,
+            interrupt_requested: false,
+            queued: VecDeque::new(),
+            scroll: 0,
+            show_intent: true,
+            approval: None,
+            approval_response: None,
+            streaming: None,
         }
     }
 
@@ -57,6 +95,123 @@ impl App {
         self.messages.push(Message { role, content });
     }
 
+    /// Apply live progress from a running request to the in-progress assistant message
+    pub fn apply_event(&mut self, event: AgentEvent) {
+        // Approvals open the modal instead of touching the transcript
+        if let AgentEvent::ApprovalRequested { id, tool, arguments, preview } = event {
+            self.approval = Some(PendingApproval { id, tool, arguments, preview, reason: None });
+            return;
+        }
+        // Token counts are only reported by headless runs
+        if matches!(event, AgentEvent::Usage { .. }) {
+            return;
+        }
+
+        let index = match self.streaming {
+            Some(index) => index,
+            None => {
+                self.add_message(MessageRole::Assistant, String::new());
+                let index = self.messages.len() - 1;
+                self.streaming = Some(index);
+                index
+            }
+        };
+        let content = &mut self.messages[index].content;
+
+        match event {
+            AgentEvent::Routed { intent, agent, via } => {
+                // Same header the final response carries, so nothing jumps when it lands
+                if self.show_intent {
+                    content.push_str(&format!("Intent: {} ({}) → [{}]\n\n", intent, via, agent));
+                } else {
+                    content.push_str(&format!("[{}]\n\n", agent));
+                }
+            }
+            AgentEvent::Text(text) => content.push_str(&text),
+            AgentEvent::ToolCall { name, arguments } => {
+                if !content.is_empty() && !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push_str(&events::tool_call_label(&name, &arguments));
+                content.push_str(events::RUNNING_MARKER);
+                content.push('\n');
+            }
+            AgentEvent::ToolResult { name, result } => {
+                // Swap the first running marker for this tool with the result preview
+                let label = format!("⏺ {}(", name);
+                let marker_pos = content.match_indices(&label).find_map(|(start, _)| {
+                    let line_end = content[start..].find('\n').map_or(content.len(), |i| start + i);
+                    content[start..line_end]
+                        .ends_with(events::RUNNING_MARKER)
+                        .then(|| line_end - events::RUNNING_MARKER.len())
+                });
+                if let Some(pos) = marker_pos {
+                    let preview = format!("\n{}", events::tool_result_preview(&result));
+                    content.replace_range(pos..pos + events::RUNNING_MARKER.len(), &preview);
+                }
+            }
+            AgentEvent::ApprovalRequested { .. } | AgentEvent::Usage { .. } => {}
+        }
+    }
+
+    /// Finish the current request: the final response replaces any streamed message
+    pub fn finish_response(&mut self, response: String) {
+        match self.streaming.take() {
+            Some(index) => self.messages[index].content = response,
+            None => self.add_message(MessageRole::Assistant, response),
+        }
+    }
+
+    /// Replace the visible transcript with a stored session's messages
+    pub fn load_transcript(&mut self, entries: &[SessionEntry]) {
+        self.messages.truncate(1);
+        self.streaming = None;
+        for entry in entries {
+            match entry {
+                SessionEntry::User { content } => self.add_message(MessageRole::User, content.clone()),
+                SessionEntry::Assistant { content } => self.add_message(MessageRole::Assistant, content.clone()),
+                SessionEntry::System { content } => self.add_message(MessageRole::System, content.clone()),
+                // Routing and tool activity are already part of the assistant output
+                SessionEntry::Route { .. } | SessionEntry::ToolCall { .. } => {}
+            }
+        }
+    }
+
+    /// Answer the open approval modal
+    fn decide(&mut self, decision: ApprovalDecision) {
+        if let Some(approval) = self.approval.take() {
+            self.approval_response = Some((approval.id, decision));
+        }
+    }
+
+    /// Keys while the approval modal is open: y/Enter approve once, a approve for the
+    /// session, n deny with a reason, Esc deny
+    fn handle_approval_key(&mut self, code: KeyCode) {
+        let Some(approval) = self.approval.as_mut() else { return };
+
+        match approval.reason.as_mut() {
+            Some(reason) => match code {
+                KeyCode::Char(c) => reason.push(c),
+                KeyCode::Backspace => {
+                    reason.pop();
+                }
+                KeyCode::Enter => {
+                    let reason = reason.clone();
+                    self.decide(ApprovalDecision::Deny(reason));
+                }
+                KeyCode::Esc => approval.reason = None,
+                _ => {}
+            },
+            None => match code {
+                KeyCode::Char('y') | KeyCode::Enter => self.decide(ApprovalDecision::Once),
+                KeyCode::Char('a') => self.decide(ApprovalDecision::Session),
+                KeyCode::Char('n') => approval.reason = Some(String::new()),
+                KeyCode::Esc => self.decide(ApprovalDecision::Deny(String::new())),
+                _ => {}
+            },
+        }
+    }
+
     pub fn submit_input(&mut self) -> Option<String> {
         if self.input.is_empty() {
             return None;
@@ -99,6 +254,10 @@ impl UI {
 
             render_messages(f, app, chunks[0]);
             render_input(f, app, chunks[1]);
+
+            if let Some(approval) = &app.approval {
+                render_approval(f, approval, size);
+            }
         })?;
 
         // Position cursor in the input box
@@ -112,14 +271,28 @@ impl UI {
         Ok(())
     }
 
-    pub fn handle_events(&self, app: &mut App) -> Result<()> {
-        if event::poll(std::time::Duration::from_millis(100))? {
+    /// Handle all pending terminal events, waiting up to `timeout` for the first one
+    pub fn handle_events(&self, app: &mut App, timeout: Duration) -> Result<()> {
+        let mut timeout = timeout;
+        while event::poll(timeout)? {
+            timeout = Duration::ZERO;
+
+            // Resize needs no handling beyond the redraw every loop iteration does
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    let quit = key.code == KeyCode::Char('c') && key.modifiers.contains(event::KeyModifiers::CONTROL);
+                    if app.approval.is_some() && !quit {
+                        app.handle_approval_key(key.code);
+                        continue;
+                    }
+
                     match key.code {
                         KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                             app.should_quit = true;
                         }
+                        KeyCode::Esc if app.is_processing => {
+                            app.interrupt_requested = true;
+                        }
                         KeyCode::Char(c) => {
                             app.input.push(c);
                         }
@@ -127,10 +300,18 @@ impl UI {
                             app.input.pop();
                         }
                         KeyCode::Enter => {
+                            // Everything goes through the queue; the main loop sends it
+                            // right away when idle, or after the current request otherwise
                             if let Some(input) = app.submit_input() {
-                                app.add_message(MessageRole::User, input);
+                                app.queued.push_back(input);
+                                app.scroll = 0;
                             }
                         }
+                        KeyCode::Up => app.scroll = app.scroll.saturating_add(1),
+                        KeyCode::Down => app.scroll = app.scroll.saturating_sub(1),
+                        KeyCode::PageUp => app.scroll = app.scroll.saturating_add(10),
+                        KeyCode::PageDown => app.scroll = app.scroll.saturating_sub(10),
+                        KeyCode::End => app.scroll = 0,
                         _ => {}
                     }
                 }
@@ -175,10 +356,16 @@ fn render_messages(f: &mut Frame, app: &App, area: Rect) {
     }
 
     // Add working indicator if processing
-    if app.is_processing {
+    if app.interrupt_requested {
+        text.push_str("✢ Interrupting…\n");
+    } else if app.is_processing {
         text.push_str("✢ Working… (esc to interrupt)\n");
     }
 
+    for queued in &app.queued {
+        text.push_str(&format!("  ⧗ queued: {}\n", queued));
+    }
+
     // Count total lines (accounting for wrapping would be complex, so we approximate)
     let content_lines = text.lines().count() as u16;
     let available_height = area.height;
@@ -197,8 +384,10 @@ fn render_messages(f: &mut Frame, app: &App, area: Rect) {
         text = padded_text;
         scroll_offset = 0; // No scroll needed when padded
     } else {
-        // Content is larger than viewport, scroll to show the bottom (newest)
-        scroll_offset = content_lines_with_padding.saturating_sub(available_height);
+        // Content is larger than viewport, scroll to show the bottom (newest),
+        // or further up if the user has scrolled
+        let max_offset = content_lines_with_padding.saturating_sub(available_height);
+        scroll_offset = max_offset.saturating_sub(app.scroll);
     }
 
     let paragraph = Paragraph::new(text)
@@ -209,21 +398,66 @@ fn render_messages(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_input(f: &mut Frame, app: &App, area: Rect) {
-    let input_text = if app.is_processing {
-        String::new()
+    let title = if app.is_processing {
+        " Input (Enter to queue, Esc to interrupt, Ctrl+C to quit) "
     } else {
-        app.input.clone()
+        " Input (Enter to send, Ctrl+C to quit) "
     };
 
-    let input = Paragraph::new(input_text)
+    let input = Paragraph::new(app.input.clone())
         .style(Style::default().fg(Color::White))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Input (Enter to send, Ctrl+C to quit) ")
+                .title(title)
                 .style(Style::default().fg(Color::Cyan))
         )
         .wrap(Wrap { trim: false });
 
     f.render_widget(input, area);
 }
+
+fn render_approval(f: &mut Frame, approval: &PendingApproval, area: Rect) {
+    // Centered box over the transcript
+    let width = area.width.saturating_mul(4) / 5;
+    let height = area.height.saturating_mul(7) / 10;
+    let modal = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let arguments = serde_json::to_string_pretty(&approval.arguments).unwrap_or_default();
+    let mut arguments: Vec<&str> = arguments.lines().collect();
+    if arguments.len() > 12 {
+        arguments.truncate(12);
+        arguments.push("  …");
+    }
+
+    let mut text = format!("Arguments:\n{}\n", arguments.join("\n"));
+    if let Some(preview) = &approval.preview {
+        text.push_str(&format!("\n{}\n", preview.trim_end()));
+    }
+    text.push('\n');
+    match &approval.reason {
+        Some(reason) => text.push_str(&format!(
+            "Reason for denying: {}▏\n(Enter to send, Esc to go back)",
+            reason
+        )),
+        None => text.push_str("[y] approve once   [a] approve for this session   [n] deny with reason   [esc] deny"),
+    }
+
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Allow {}? ", approval.tool))
+                .style(Style::default().fg(Color::Yellow))
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, modal);
+    f.render_widget(paragraph, modal);
+}
@@ -0,0 +1,101 @@
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+/// Watches the config and instruction files so edits apply without a restart
+pub struct ConfigWatcher {
+    /// Watching stops when this is dropped
+    watcher: RecommendedWatcher,
+    files: BTreeSet<PathBuf>,
+    /// Each file's directory, or its nearest existing ancestor until the directory
+    /// is created (a project without `.ada/` yet)
+    watched: BTreeSet<PathBuf>,
+    /// Changed files, and directories on the way to them that appeared or went away
+    changes: mpsc::UnboundedReceiver<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Watch `files`, which need not exist yet. Directories are watched rather than
+    /// the files themselves, so files created later and editors that replace a file
+    /// on save are both noticed.
+    pub fn new(files: Vec<PathBuf>) -> Result<Self> {
+        let files: BTreeSet<PathBuf> = files.into_iter().collect();
+
+        let (sender, changes) = mpsc::unbounded_channel();
+        let targets = files.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else { return };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            for path in event.paths {
+                if targets.iter().any(|file| file.starts_with(&path)) {
+                    let _ = sender.send(path);
+                }
+            }
+        })
+        .context("Failed to start the file watcher")?;
+
+        let mut watcher = Self {
+            watcher,
+            files,
+            watched: BTreeSet::new(),
+            changes,
+        };
+        watcher.arm()?;
+        Ok(watcher)
+    }
+
+    /// Files that changed since the last call, without waiting
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        let mut dirs_changed = false;
+        while let Ok(path) = self.changes.try_recv() {
+            if !self.files.contains(&path) {
+                // A removed directory loses its watch; forget it so it is watched
+                // again if it comes back
+                if self.watched.remove(&path) {
+                    let _ = self.watcher.unwatch(&path);
+                }
+                dirs_changed = true;
+            } else if !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+
+        // Move the watch closer to the files, and pick up any written into a new
+        // directory before it was watched
+        if dirs_changed {
+            for dir in self.arm().unwrap_or_default() {
+                for file in self.files.iter().filter(|file| file.starts_with(&dir) && file.exists()) {
+                    if !changed.contains(file) {
+                        changed.push(file.clone());
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    /// Watch the nearest existing directory of every file, returning the ones newly
+    /// watched
+    fn arm(&mut self) -> Result<Vec<PathBuf>> {
+        let mut armed = Vec::new();
+        for file in &self.files {
+            let Some(dir) = file.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+                continue;
+            };
+            if self.watched.contains(dir) {
+                continue;
+            }
+            self.watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {}", dir.display()))?;
+            self.watched.insert(dir.to_path_buf());
+            armed.push(dir.to_path_buf());
+        }
+        Ok(armed)
+    }
+}